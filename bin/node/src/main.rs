@@ -1,8 +1,24 @@
-use clap::Parser;
-use miden_note_transport_node::database::DatabaseConfig;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use miden_note_transport_node::database::{
+    DatabaseConfig,
+    SqliteConfig,
+    SqliteJournalMode,
+    SqliteSynchronous,
+};
 use miden_note_transport_node::logging::{TracingConfig, setup_tracing};
-use miden_note_transport_node::node::grpc::GrpcServerConfig;
+use miden_note_transport_node::metrics::MetricsConfig;
+use miden_note_transport_node::node::grpc::{
+    GrpcServerConfig,
+    SlowSubscriberPolicy,
+    StreamerConfig,
+    TagPolicy,
+    TlsConfig,
+};
 use miden_note_transport_node::{Node, NodeConfig, Result};
+use tonic::codec::CompressionEncoding;
 use tracing::info;
 
 #[derive(Parser)]
@@ -17,7 +33,7 @@ struct Args {
     #[arg(long, default_value = "57292")]
     port: u16,
 
-    /// Database URL
+    /// Database URL: `sqlite:<path>` or a path, `postgres://...`, or `memory:`
     #[arg(long, default_value = ":memory:")]
     database_url: String,
 
@@ -25,17 +41,259 @@ struct Args {
     #[arg(long, default_value = "30")]
     retention_days: u32,
 
-    /// Maximum note size in bytes
+    /// Interval between database maintenance runs in seconds
+    #[arg(long, default_value = "600")]
+    maintenance_interval_secs: u64,
+
+    /// Delay the first database maintenance run by one interval instead of running it on start
+    #[arg(long)]
+    maintenance_delay_first_run: bool,
+
+    /// Maximum number of pooled database connections
+    #[arg(long, default_value = "16")]
+    database_pool_size: usize,
+
+    /// `SQLite` journal mode (delete, truncate, persist, memory, wal or off)
+    #[arg(long, default_value = "wal")]
+    sqlite_journal_mode: SqliteJournalMode,
+
+    /// `SQLite` synchronous mode (off, normal, full or extra)
+    #[arg(long, default_value = "normal")]
+    sqlite_synchronous: SqliteSynchronous,
+
+    /// `SQLite` busy timeout in milliseconds
+    #[arg(long, default_value = "30000")]
+    sqlite_busy_timeout_ms: u64,
+
+    /// Maximum note size in bytes, header and details included
     #[arg(long, default_value = "512000")]
     max_note_size: usize,
 
+    /// Note size in bytes above which notes are counted as near the limit, 0 to disable
+    #[arg(long, default_value = "409600")]
+    warn_note_size: usize,
+
+    /// Maximum note header size in bytes
+    #[arg(long, default_value = "256")]
+    max_header_size: usize,
+
     /// Maximum number of concurrent connections
     #[arg(long, default_value = "4096")]
     max_connections: usize,
 
-    /// Connection timeout in seconds
-    #[arg(long, default_value = "4")]
-    request_timeout: usize,
+    /// Request timeout in seconds
+    #[arg(long, alias = "request-timeout", default_value = "4")]
+    request_timeout_seconds: usize,
+
+    /// Maximum number of tags reported in the per-tag statistics
+    #[arg(long, default_value = "100")]
+    stats_max_tags: u32,
+
+    /// Maximum number of notes sent per minute for a single tag, 0 for unlimited
+    #[arg(long, default_value = "600")]
+    rate_limit_per_minute: u32,
+
+    /// Maximum number of notes sent per minute for all tags combined, 0 for unlimited
+    #[arg(long, default_value = "60000")]
+    global_rate_limit_per_minute: u32,
+
+    /// Interval in milliseconds between streaming queries for new notes
+    #[arg(long, default_value = "500")]
+    stream_poll_interval_ms: u64,
+
+    /// Maximum number of notes streamed per tag and poll, unlimited if unset
+    #[arg(long)]
+    stream_max_batch: Option<u32>,
+
+    /// Time in milliseconds to wait for a slow streaming subscriber before dropping it, dropped
+    /// immediately if unset
+    #[arg(long)]
+    stream_lag_timeout_ms: Option<u64>,
+
+    /// Interval in milliseconds without streamed notes after which an empty update is sent,
+    /// disabled if unset
+    #[arg(long)]
+    stream_heartbeat_interval_ms: Option<u64>,
+
+    /// Interval between HTTP/2 keepalive pings in milliseconds, 0 to disable
+    #[arg(long, default_value = "30000")]
+    http2_keepalive_interval_ms: u64,
+
+    /// Time in milliseconds waited for a keepalive ping acknowledgment
+    #[arg(long, default_value = "20000")]
+    http2_keepalive_timeout_ms: u64,
+
+    /// Enable note acknowledgment, allowing fetches to exclude acknowledged notes
+    #[arg(long)]
+    enable_acks: bool,
+
+    /// Time in seconds during which a send-note idempotency key is remembered
+    #[arg(long, default_value = "600")]
+    idempotency_ttl_secs: u64,
+
+    /// Maximum number of cached send-note responses by idempotency key, 0 to disable idempotency
+    /// keys
+    #[arg(long, default_value = "10000")]
+    idempotency_capacity: usize,
+
+    /// Comma-separated origins allowed for gRPC-web requests, any origin if unset
+    #[arg(long, value_delimiter = ',')]
+    cors_allowed_origins: Option<Vec<String>>,
+
+    /// Comma-separated request headers allowed for gRPC-web requests, any header if unset
+    #[arg(long, value_delimiter = ',')]
+    cors_allowed_headers: Option<Vec<String>>,
+
+    /// Comma-separated methods allowed for gRPC-web requests, any method if unset
+    #[arg(long, value_delimiter = ',')]
+    cors_allowed_methods: Option<Vec<String>>,
+
+    /// Server TLS certificate chain (PEM), serving TLS if set along with the key
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Server TLS private key (PEM)
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// CA certificate (PEM) required to have issued client certificates, enabling mTLS
+    #[arg(long, requires = "tls_cert")]
+    tls_client_ca: Option<PathBuf>,
+
+    /// Bearer token accepted for requests, can be repeated. No authentication if unset
+    #[arg(long = "auth-token")]
+    auth_tokens: Vec<String>,
+
+    /// Time in milliseconds during which a fetch-notes result is cached
+    #[arg(long, default_value = "1000")]
+    fetch_cache_ttl_ms: u64,
+
+    /// Maximum number of cached fetch-notes results, 0 to disable the cache
+    #[arg(long, default_value = "0")]
+    fetch_cache_capacity: usize,
+
+    /// Maximum number of notes per fetch-notes response, 0 for unlimited
+    #[arg(long, default_value = "1000")]
+    max_fetch_limit: u32,
+
+    /// Maximum number of open note streams, 0 for unlimited
+    #[arg(long, default_value = "10000")]
+    max_subscriptions: usize,
+
+    /// Maximum number of open note streams per peer IP address, 0 for unlimited
+    #[arg(long, default_value = "100")]
+    max_subscriptions_per_peer: usize,
+
+    /// Maximum number of note IDs of an exists-notes or ack-notes request
+    #[arg(long, default_value = "1000")]
+    max_note_ids: usize,
+
+    /// Reject notes that are not private
+    #[arg(long)]
+    require_private_notes: bool,
+
+    /// Compression of responses, if accepted by the client
+    #[arg(long, value_enum, default_value = "gzip")]
+    compression: Compression,
+
+    /// Port serving metrics in the Prometheus text format on `/metrics`, disabled if unset
+    #[arg(long)]
+    prometheus_port: Option<u16>,
+}
+
+impl Args {
+    /// Node configuration from the arguments
+    fn into_config(self, otlp_endpoint: Option<String>) -> NodeConfig {
+        NodeConfig {
+            grpc: GrpcServerConfig {
+                host: self.host,
+                port: self.port,
+                max_note_size: self.max_note_size,
+                warn_note_size: self.warn_note_size,
+                max_header_size: self.max_header_size,
+                max_connections: self.max_connections,
+                request_timeout: self.request_timeout_seconds,
+                http2_keepalive_interval: (self.http2_keepalive_interval_ms != 0)
+                    .then(|| Duration::from_millis(self.http2_keepalive_interval_ms)),
+                http2_keepalive_timeout: Duration::from_millis(self.http2_keepalive_timeout_ms),
+                stats_max_tags: self.stats_max_tags,
+                rate_limit_per_minute: self.rate_limit_per_minute,
+                global_rate_limit_per_minute: self.global_rate_limit_per_minute,
+                streamer: StreamerConfig {
+                    poll_interval: Duration::from_millis(self.stream_poll_interval_ms),
+                    max_batch: self.stream_max_batch,
+                    slow_policy: self
+                        .stream_lag_timeout_ms
+                        .map_or(SlowSubscriberPolicy::Drop, |timeout| {
+                            SlowSubscriberPolicy::Lag(Duration::from_millis(timeout))
+                        }),
+                    heartbeat_interval: self
+                        .stream_heartbeat_interval_ms
+                        .map(Duration::from_millis),
+                },
+                acks_enabled: self.enable_acks,
+                compression: self.compression.into(),
+                tag_policy: if self.require_private_notes {
+                    TagPolicy::RequirePrivate
+                } else {
+                    TagPolicy::AllowAll
+                },
+                idempotency_ttl: Duration::from_secs(self.idempotency_ttl_secs),
+                idempotency_capacity: self.idempotency_capacity,
+                cors_allowed_origins: self.cors_allowed_origins,
+                cors_allowed_headers: self.cors_allowed_headers,
+                cors_allowed_methods: self.cors_allowed_methods,
+                tls: self.tls_cert.zip(self.tls_key).map(|(server_cert, server_key)| TlsConfig {
+                    server_cert,
+                    server_key,
+                    client_ca: self.tls_client_ca,
+                }),
+                auth_tokens: self.auth_tokens,
+                fetch_cache_ttl: Duration::from_millis(self.fetch_cache_ttl_ms),
+                fetch_cache_capacity: self.fetch_cache_capacity,
+                max_fetch_limit: self.max_fetch_limit,
+                max_subscriptions: self.max_subscriptions,
+                max_subscriptions_per_peer: self.max_subscriptions_per_peer,
+                max_note_ids: self.max_note_ids,
+            },
+            database: DatabaseConfig {
+                url: self.database_url,
+                retention_days: self.retention_days,
+                maintenance_interval_secs: self.maintenance_interval_secs,
+                maintenance_run_on_start: !self.maintenance_delay_first_run,
+                pool_max_size: self.database_pool_size,
+                sqlite: SqliteConfig {
+                    journal_mode: self.sqlite_journal_mode,
+                    synchronous: self.sqlite_synchronous,
+                    busy_timeout: Duration::from_millis(self.sqlite_busy_timeout_ms),
+                    ..Default::default()
+                },
+            },
+            metrics: MetricsConfig {
+                otlp_endpoint,
+                prometheus_port: self.prometheus_port,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Response compression
+#[derive(Clone, Copy, ValueEnum)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl From<Compression> for Option<CompressionEncoding> {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::None => None,
+            Compression::Gzip => Some(CompressionEncoding::Gzip),
+            Compression::Zstd => Some(CompressionEncoding::Zstd),
+        }
+    }
 }
 
 #[tokio::main]
@@ -58,21 +316,12 @@ async fn main() -> Result<()> {
         tracing_cfg.otel.is_enabled(),
         tracing_cfg.json_format
     );
+    if let Some(port) = args.prometheus_port {
+        info!("Prometheus metrics port: {port}");
+    }
 
     // Create Node config
-    let config = NodeConfig {
-        grpc: GrpcServerConfig {
-            host: args.host,
-            port: args.port,
-            max_note_size: args.max_note_size,
-            max_connections: args.max_connections,
-            request_timeout: args.request_timeout,
-        },
-        database: DatabaseConfig {
-            url: args.database_url,
-            retention_days: args.retention_days,
-        },
-    };
+    let config = args.into_config(tracing_cfg.otel.endpoint().map(ToString::to_string));
 
     // Run Node
     let node = Node::init(config).await?;
@@ -80,3 +329,44 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_into_config() {
+        let args = Args::try_parse_from([
+            "miden-note-transport-node",
+            "--max-connections",
+            "16",
+            "--request-timeout-seconds",
+            "10",
+            "--max-note-size",
+            "1024",
+            "--retention-days",
+            "7",
+            "--stream-lag-timeout-ms",
+            "250",
+            "--cors-allowed-origins",
+            "https://a.example.com,https://b.example.com",
+        ])
+        .unwrap();
+        let config = args.into_config(None);
+
+        assert_eq!(config.grpc.max_connections, 16);
+        assert_eq!(config.grpc.request_timeout, 10);
+        assert_eq!(config.grpc.max_note_size, 1024);
+        assert_eq!(config.database.retention_days, 7);
+        assert_eq!(
+            config.grpc.streamer.slow_policy,
+            SlowSubscriberPolicy::Lag(Duration::from_millis(250))
+        );
+        assert_eq!(
+            config.grpc.cors_allowed_origins,
+            Some(vec!["https://a.example.com".to_string(), "https://b.example.com".to_string()])
+        );
+        assert!(config.grpc.cors_allowed_methods.is_none());
+        assert!(config.metrics.otlp_endpoint.is_none());
+    }
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+
+/// Source of the current time
+///
+/// Allows time-dependent behavior (note timestamps, retention) to be tested deterministically.
+pub trait Clock: Send + Sync {
+    /// Current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// System time [`Clock`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
@@ -27,6 +27,10 @@ pub enum DatabaseError {
     #[error("Deserialization error: {0}")]
     Deserialization(String),
 
+    /// Corrupted data error
+    #[error("Corrupted data: {0}")]
+    Corruption(String),
+
     /// Constraint violation error
     #[error("Constraint violation: {0}")]
     ConstraintViolation(String),
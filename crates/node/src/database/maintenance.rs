@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use tokio::time::{Duration, sleep};
+use chrono::Utc;
+use tokio::sync::watch;
+use tokio::time::{Duration, Instant, MissedTickBehavior, interval_at};
 use tracing::{error, info};
 
 use super::{Database, DatabaseConfig};
@@ -32,11 +34,27 @@ impl DatabaseMaintenance {
     }
 
     /// Database maintenance running-task
-    pub async fn entrypoint(mut self) {
+    ///
+    /// Runs until `shutdown` is set to `true` or its sender is dropped.
+    pub async fn entrypoint(mut self, mut shutdown: watch::Receiver<bool>) {
+        let period = Duration::from_secs(self.config.maintenance_interval_secs.max(1));
+        let start = if self.config.maintenance_run_on_start {
+            Instant::now()
+        } else {
+            Instant::now() + period
+        };
+        let mut interval = interval_at(start, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         self.state = State::Running;
         while self.is_active() {
-            if let Err(e) = self.step().await {
-                error!("Database maintenance error: {e}");
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.step().await {
+                        error!("Database maintenance error: {e}");
+                    }
+                },
+                _ = shutdown.wait_for(|stop| *stop) => self.state = State::Stopped,
             }
         }
     }
@@ -44,12 +62,12 @@ impl DatabaseMaintenance {
     async fn step(&mut self) -> Result<()> {
         let timer = self.metrics.db_maintenance_cleanup_notes();
 
-        self.database.cleanup_old_notes(self.config.retention_days).await?;
-        info!("Cleaned up old notes");
+        let deleted = self.database.cleanup_old_notes(self.config.retention_days).await?;
+        info!("Cleaned up {deleted} old notes");
 
         timer.finish("ok");
-
-        sleep(Duration::from_secs(600)).await;
+        let now = u64::try_from(Utc::now().timestamp()).unwrap_or(0);
+        self.metrics.db_maintenance_cleanup_notes_done(deleted, now);
 
         Ok(())
     }
@@ -61,12 +79,13 @@ impl DatabaseMaintenance {
 
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
     use serial_test::serial;
+    use tokio::time::sleep;
 
     use super::*;
+    use crate::database::tests::test_databases;
     use crate::metrics::Metrics;
-    use crate::test_utils::test_note_header;
+    use crate::test_utils::{TestMetrics, test_note_header};
     use crate::types::StoredNote;
 
     fn note_at(age: Duration) -> StoredNote {
@@ -74,6 +93,9 @@ mod tests {
             header: test_note_header(),
             details: vec![1, 2, 3, 4],
             created_at: Utc::now() - age,
+            expires_at: None,
+            seq: None,
+            format_version: 1,
         }
     }
 
@@ -82,15 +104,19 @@ mod tests {
     async fn test_cleanup_old_notes_no_retention() {
         let config = DatabaseConfig { retention_days: 0, ..Default::default() };
 
-        let db = Arc::new(Database::connect(config.clone(), Metrics::default().db).await.unwrap());
-        db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
+        for db in test_databases(config.clone()).await {
+            let db = Arc::new(db);
+            db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
 
-        let maintenance = DatabaseMaintenance::new(db.clone(), config, Metrics::default().db);
-        tokio::spawn(maintenance.entrypoint());
-        sleep(Duration::from_secs(2)).await;
+            let maintenance =
+                DatabaseMaintenance::new(db.clone(), config.clone(), Metrics::default().db);
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            tokio::spawn(maintenance.entrypoint(shutdown_rx));
+            sleep(Duration::from_secs(2)).await;
 
-        let (total_notes, _) = db.get_stats().await.unwrap();
-        assert_eq!(total_notes, 0);
+            let (total_notes, _) = db.get_stats().await.unwrap();
+            assert_eq!(total_notes, 0);
+        }
     }
 
     #[tokio::test]
@@ -98,15 +124,19 @@ mod tests {
     async fn test_cleanup_old_notes_retention() {
         let config = DatabaseConfig { retention_days: 7, ..Default::default() };
 
-        let db = Arc::new(Database::connect(config.clone(), Metrics::default().db).await.unwrap());
-        db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
+        for db in test_databases(config.clone()).await {
+            let db = Arc::new(db);
+            db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
 
-        let maintenance = DatabaseMaintenance::new(db.clone(), config, Metrics::default().db);
-        tokio::spawn(maintenance.entrypoint());
-        sleep(Duration::from_secs(2)).await;
+            let maintenance =
+                DatabaseMaintenance::new(db.clone(), config.clone(), Metrics::default().db);
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            tokio::spawn(maintenance.entrypoint(shutdown_rx));
+            sleep(Duration::from_secs(2)).await;
 
-        let (total_notes, _) = db.get_stats().await.unwrap();
-        assert_eq!(total_notes, 1);
+            let (total_notes, _) = db.get_stats().await.unwrap();
+            assert_eq!(total_notes, 1);
+        }
     }
 
     #[tokio::test]
@@ -114,15 +144,84 @@ mod tests {
     async fn test_cleanup_old_notes_mixed_ages() {
         let config = DatabaseConfig { retention_days: 1, ..Default::default() };
 
-        let db = Arc::new(Database::connect(config.clone(), Metrics::default().db).await.unwrap());
-        db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
-        db.store_note(&note_at(Duration::from_secs(3600 * 26))).await.unwrap();
+        for db in test_databases(config.clone()).await {
+            let db = Arc::new(db);
+            db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
+            db.store_note(&note_at(Duration::from_secs(3600 * 26))).await.unwrap();
 
-        let maintenance = DatabaseMaintenance::new(db.clone(), config, Metrics::default().db);
-        tokio::spawn(maintenance.entrypoint());
-        sleep(Duration::from_secs(2)).await;
+            let maintenance =
+                DatabaseMaintenance::new(db.clone(), config.clone(), Metrics::default().db);
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            tokio::spawn(maintenance.entrypoint(shutdown_rx));
+            sleep(Duration::from_secs(2)).await;
+
+            let (total_notes, _) = db.get_stats().await.unwrap();
+            assert_eq!(total_notes, 1);
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_maintenance_notes_deleted_metric() {
+        let config = DatabaseConfig { retention_days: 1, ..Default::default() };
+
+        for db in test_databases(config.clone()).await {
+            let db = Arc::new(db);
+            db.store_note(&note_at(Duration::from_secs(30))).await.unwrap();
+            for _ in 0..2 {
+                db.store_note(&note_at(Duration::from_secs(3600 * 26))).await.unwrap();
+            }
 
-        let (total_notes, _) = db.get_stats().await.unwrap();
-        assert_eq!(total_notes, 1);
+            let metrics = TestMetrics::default();
+            let mut maintenance =
+                DatabaseMaintenance::new(db.clone(), config.clone(), metrics.metrics.db.clone());
+            maintenance.step().await.unwrap();
+
+            assert_eq!(metrics.counter("db_maintenance_notes_deleted_count"), 2);
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_maintenance_interval() {
+        let config = DatabaseConfig {
+            maintenance_interval_secs: 1,
+            maintenance_run_on_start: false,
+            ..Default::default()
+        };
+
+        for db in test_databases(config.clone()).await {
+            let metrics = TestMetrics::default();
+            let maintenance =
+                DatabaseMaintenance::new(Arc::new(db), config.clone(), metrics.metrics.db.clone());
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            tokio::spawn(maintenance.entrypoint(shutdown_rx));
+
+            // Not run on start
+            sleep(Duration::from_millis(500)).await;
+            assert_eq!(metrics.counter("db_maintenance_cleanup_notes_count"), 0);
+
+            sleep(Duration::from_millis(2000)).await;
+            assert!(metrics.counter("db_maintenance_cleanup_notes_count") >= 2);
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_maintenance_shutdown() {
+        let config = DatabaseConfig::default();
+
+        for db in test_databases(config.clone()).await {
+            let maintenance =
+                DatabaseMaintenance::new(Arc::new(db), config.clone(), Metrics::default().db);
+            let (shutdown_tx, shutdown_rx) = watch::channel(false);
+            let handle = tokio::spawn(maintenance.entrypoint(shutdown_rx));
+
+            shutdown_tx.send(true).unwrap();
+            tokio::time::timeout(Duration::from_secs(5), handle)
+                .await
+                .expect("maintenance should stop")
+                .unwrap();
+        }
     }
 }
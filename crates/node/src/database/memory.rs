@@ -0,0 +1,454 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+
+use crate::database::{DatabaseBackend, DatabaseConfig, DatabaseError, IdempotencyKey};
+use crate::metrics::MetricsDatabase;
+use crate::types::{NoteId, NoteTag, StoredNote};
+
+/// In-memory implementation of the database backend
+///
+/// Notes are kept in process memory and lost on exit. Intended for tests and ephemeral nodes.
+pub struct MemoryDatabase {
+    state: Mutex<MemoryState>,
+    metrics: MetricsDatabase,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    /// Notes by sequence number
+    notes: BTreeMap<u64, MemoryNote>,
+    /// Sequence numbers by note ID
+    ids: BTreeMap<[u8; 32], u64>,
+    /// Sequence numbers by tag
+    tags: BTreeMap<u32, BTreeSet<u64>>,
+    /// Recorded idempotency keys
+    idempotency_keys: BTreeMap<String, MemoryIdempotencyKey>,
+    /// Last assigned sequence number, never reused
+    last_seq: u64,
+}
+
+struct MemoryNote {
+    note: StoredNote,
+    acked_at: Option<DateTime<Utc>>,
+}
+
+struct MemoryIdempotencyKey {
+    /// Sequence number of the note of the keyed request
+    seq: u64,
+    duplicate: bool,
+    created_at: DateTime<Utc>,
+}
+
+impl MemoryNote {
+    /// Whether the note is served at `now`
+    fn visible(&self, now: DateTime<Utc>, exclude_acked: bool) -> bool {
+        self.note.expires_at.is_none_or(|expires_at| expires_at > now)
+            && !(exclude_acked && self.acked_at.is_some())
+    }
+}
+
+impl MemoryState {
+    /// Store a note, unless a note with the same ID is already stored
+    fn insert(&mut self, note: &StoredNote) -> bool {
+        let id = note.header.id().as_bytes();
+        if self.ids.contains_key(&id) {
+            return false;
+        }
+
+        self.last_seq += 1;
+        let seq = self.last_seq;
+        // Same precision as the SQL backends
+        let note = StoredNote {
+            created_at: truncate_micros(note.created_at),
+            expires_at: note.expires_at.map(truncate_micros),
+            seq: Some(seq),
+            ..note.clone()
+        };
+        self.ids.insert(id, seq);
+        self.tags.entry(note.header.metadata().tag().as_u32()).or_default().insert(seq);
+        self.notes.insert(seq, MemoryNote { note, acked_at: None });
+        true
+    }
+
+    fn remove(&mut self, seq: u64) {
+        let Some(entry) = self.notes.remove(&seq) else {
+            return;
+        };
+        self.ids.remove(&entry.note.header.id().as_bytes());
+        self.idempotency_keys.retain(|_, key| key.seq != seq);
+        let tag = entry.note.header.metadata().tag().as_u32();
+        if let Some(seqs) = self.tags.get_mut(&tag) {
+            seqs.remove(&seq);
+            if seqs.is_empty() {
+                self.tags.remove(&tag);
+            }
+        }
+    }
+
+    /// Notes visible at `now` with any of `tags` stored after `cursor`, in sequence order
+    fn after(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        exclude_acked: bool,
+        now: DateTime<Utc>,
+    ) -> impl Iterator<Item = &MemoryNote> {
+        let mut seqs: Vec<u64> = tags
+            .iter()
+            .filter_map(|tag| self.tags.get(&tag.as_u32()))
+            .flat_map(|seqs| seqs.range((Bound::Excluded(cursor), Bound::Unbounded)))
+            .copied()
+            .collect();
+        seqs.sort_unstable();
+        seqs.dedup();
+
+        seqs.into_iter()
+            .filter_map(|seq| self.notes.get(&seq))
+            .filter(move |entry| entry.visible(now, exclude_acked))
+    }
+
+    /// Notes visible at `now` with any of `tags` stored before `cursor`, or all if `0`, most recent
+    /// first
+    fn before(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        exclude_acked: bool,
+        now: DateTime<Utc>,
+    ) -> impl Iterator<Item = &MemoryNote> {
+        let upper = if cursor == 0 {
+            Bound::Unbounded
+        } else {
+            Bound::Excluded(cursor)
+        };
+        let mut seqs: Vec<u64> = tags
+            .iter()
+            .filter_map(|tag| self.tags.get(&tag.as_u32()))
+            .flat_map(|seqs| seqs.range((Bound::Unbounded, upper)))
+            .copied()
+            .collect();
+        seqs.sort_unstable();
+        seqs.dedup();
+
+        seqs.into_iter()
+            .rev()
+            .filter_map(|seq| self.notes.get(&seq))
+            .filter(move |entry| entry.visible(now, exclude_acked))
+    }
+}
+
+impl MemoryDatabase {
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().expect("Memory database lock poisoned")
+    }
+
+    /// Fetch notes, optionally excluding acknowledged ones
+    ///
+    /// In descending order, notes stored before `cursor` are fetched, or all notes if `0`.
+    fn fetch(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        limit: Option<u32>,
+        exclude_acked: bool,
+        descending: bool,
+        now: DateTime<Utc>,
+    ) -> Vec<StoredNote> {
+        let timer = self.metrics.db_fetch_notes();
+
+        let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+        let state = self.state();
+        let notes = if descending {
+            state
+                .before(tags, cursor, exclude_acked, now)
+                .take(limit)
+                .map(|entry| entry.note.clone())
+                .collect()
+        } else {
+            state
+                .after(tags, cursor, exclude_acked, now)
+                .take(limit)
+                .map(|entry| entry.note.clone())
+                .collect()
+        };
+        drop(state);
+
+        timer.finish("ok");
+        notes
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabaseBackend for MemoryDatabase {
+    async fn connect(
+        _config: DatabaseConfig,
+        metrics: MetricsDatabase,
+    ) -> Result<Self, DatabaseError> {
+        Ok(Self {
+            state: Mutex::new(MemoryState::default()),
+            metrics,
+        })
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "db.store_note"))]
+    async fn store_note(&self, note: &StoredNote) -> Result<(), DatabaseError> {
+        let timer = self.metrics.db_store_note();
+
+        if !self.state().insert(note) {
+            return Err(DatabaseError::ConstraintViolation(format!(
+                "Note {} already stored",
+                note.header.id()
+            )));
+        }
+
+        timer.finish("ok");
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "db.store_note"))]
+    async fn store_note_with_metadata(
+        &self,
+        note: &StoredNote,
+        idempotency_key: Option<IdempotencyKey<'_>>,
+    ) -> Result<bool, DatabaseError> {
+        let timer = self.metrics.db_store_note();
+
+        // Checked before any write, the state lock making both writes atomic
+        let mut state = self.state();
+        if let Some(IdempotencyKey { key, expired_before }) = idempotency_key {
+            if state
+                .idempotency_keys
+                .get(key)
+                .is_some_and(|key| key.created_at >= expired_before)
+            {
+                return Err(DatabaseError::ConstraintViolation(format!(
+                    "Idempotency key {key} already stored"
+                )));
+            }
+        }
+        let inserted = state.insert(note);
+        if let Some(IdempotencyKey { key, .. }) = idempotency_key {
+            let seq = state.ids[&note.header.id().as_bytes()];
+            let key_entry = MemoryIdempotencyKey {
+                seq,
+                duplicate: !inserted,
+                created_at: truncate_micros(note.created_at),
+            };
+            state.idempotency_keys.insert(key.to_string(), key_entry);
+        }
+        drop(state);
+
+        timer.finish("ok");
+        Ok(inserted)
+    }
+
+    async fn fetch_idempotency_key(
+        &self,
+        idempotency_key: IdempotencyKey<'_>,
+    ) -> Result<Option<bool>, DatabaseError> {
+        let state = self.state();
+        Ok(state
+            .idempotency_keys
+            .get(idempotency_key.key)
+            .filter(|key| key.created_at >= idempotency_key.expired_before)
+            .map(|key| key.duplicate))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
+    async fn fetch_notes(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        limit: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<StoredNote>, DatabaseError> {
+        Ok(self.fetch(tags, cursor, limit, false, false, now))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
+    async fn fetch_unacked_notes(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        limit: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<StoredNote>, DatabaseError> {
+        Ok(self.fetch(tags, cursor, limit, true, false, now))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
+    async fn fetch_notes_desc(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        limit: Option<u32>,
+        exclude_acked: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<StoredNote>, DatabaseError> {
+        Ok(self.fetch(tags, cursor, limit, exclude_acked, true, now))
+    }
+
+    async fn count_notes_after(
+        &self,
+        tags: &[NoteTag],
+        cursor: u64,
+        exclude_acked: bool,
+        now: DateTime<Utc>,
+    ) -> Result<u64, DatabaseError> {
+        let count = self.state().after(tags, cursor, exclude_acked, now).count();
+        Ok(count as u64)
+    }
+
+    async fn mark_acked(
+        &self,
+        note_ids: &[NoteId],
+        now: DateTime<Utc>,
+    ) -> Result<u64, DatabaseError> {
+        let mut state = self.state();
+        let MemoryState { notes, ids, .. } = &mut *state;
+
+        let mut acked_count = 0;
+        for note_id in note_ids {
+            let Some(entry) = ids.get(&note_id.as_bytes()).and_then(|seq| notes.get_mut(seq))
+            else {
+                continue;
+            };
+            if entry.acked_at.is_none() {
+                entry.acked_at = Some(now);
+                acked_count += 1;
+            }
+        }
+
+        Ok(acked_count)
+    }
+
+    async fn latest_seq(&self) -> Result<u64, DatabaseError> {
+        Ok(self.state().notes.keys().next_back().copied().unwrap_or(0))
+    }
+
+    async fn get_stats(&self) -> Result<(u64, u64), DatabaseError> {
+        let state = self.state();
+        Ok((state.notes.len() as u64, state.tags.len() as u64))
+    }
+
+    async fn notes_per_tag(&self, limit: u32) -> Result<Vec<(NoteTag, u64)>, DatabaseError> {
+        let mut counts: Vec<(u32, u64)> =
+            self.state().tags.iter().map(|(tag, seqs)| (*tag, seqs.len() as u64)).collect();
+        counts.sort_by(|(tag_a, count_a), (tag_b, count_b)| {
+            count_b.cmp(count_a).then(tag_a.cmp(tag_b))
+        });
+
+        Ok(counts
+            .into_iter()
+            .take(limit as usize)
+            .map(|(tag, count)| (NoteTag::from(tag), count))
+            .collect())
+    }
+
+    async fn cleanup_old_notes(
+        &self,
+        retention_days: u32,
+        now: DateTime<Utc>,
+    ) -> Result<u64, DatabaseError> {
+        let cutoff_date = now - chrono::Duration::days(i64::from(retention_days));
+
+        let mut state = self.state();
+        let expired: Vec<u64> = state
+            .notes
+            .iter()
+            .filter(|(_, entry)| {
+                entry.note.created_at < cutoff_date
+                    || entry.note.expires_at.is_some_and(|expires_at| expires_at <= now)
+            })
+            .map(|(seq, _)| *seq)
+            .collect();
+        for seq in &expired {
+            state.remove(*seq);
+        }
+
+        Ok(expired.len() as u64)
+    }
+
+    async fn get_note(
+        &self,
+        note_id: NoteId,
+        now: DateTime<Utc>,
+    ) -> Result<Option<StoredNote>, DatabaseError> {
+        let state = self.state();
+        Ok(state
+            .ids
+            .get(&note_id.as_bytes())
+            .and_then(|seq| state.notes.get(seq))
+            .filter(|entry| entry.visible(now, false))
+            .map(|entry| entry.note.clone()))
+    }
+
+    async fn note_exists(&self, note_id: NoteId) -> Result<bool, DatabaseError> {
+        Ok(self.state().ids.contains_key(&note_id.as_bytes()))
+    }
+
+    async fn existing_notes(&self, note_ids: &[NoteId]) -> Result<Vec<NoteId>, DatabaseError> {
+        let state = self.state();
+        let mut seen = BTreeSet::new();
+        Ok(note_ids
+            .iter()
+            .filter(|note_id| {
+                let id = note_id.as_bytes();
+                state.ids.contains_key(&id) && seen.insert(id)
+            })
+            .copied()
+            .collect())
+    }
+
+    async fn list_tags(&self, limit: u32, offset: u32) -> Result<Vec<NoteTag>, DatabaseError> {
+        Ok(self
+            .state()
+            .tags
+            .keys()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|tag| NoteTag::from(*tag))
+            .collect())
+    }
+
+    async fn note_histogram(
+        &self,
+        bucket_secs: u32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, DatabaseError> {
+        let bucket_micros = i64::from(bucket_secs) * 1_000_000;
+
+        // Same truncating division as the SQL backends
+        let mut buckets: BTreeMap<i64, u64> = BTreeMap::new();
+        for entry in self.state().notes.values() {
+            let created_at = entry.note.created_at;
+            if created_at >= from && created_at < to {
+                *buckets.entry(created_at.timestamp_micros() / bucket_micros).or_default() += 1;
+            }
+        }
+
+        buckets
+            .into_iter()
+            .map(|(bucket, count)| {
+                let start = bucket
+                    .checked_mul(bucket_micros)
+                    .and_then(DateTime::from_timestamp_micros)
+                    .ok_or_else(|| {
+                        DatabaseError::Deserialization(format!(
+                            "Invalid histogram bucket: {bucket}"
+                        ))
+                    })?;
+                Ok((start, count))
+            })
+            .collect()
+    }
+}
+
+/// Truncate a timestamp to microseconds
+fn truncate_micros(timestamp: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(timestamp.timestamp_micros()).unwrap_or(timestamp)
+}
@@ -0,0 +1,10 @@
+CREATE TABLE notes (
+    id BYTEA PRIMARY KEY,
+    tag BIGINT NOT NULL,
+    header BYTEA NOT NULL,
+    details BYTEA NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX idx_notes_tag ON notes(tag);
+CREATE INDEX idx_notes_created_at ON notes(created_at);
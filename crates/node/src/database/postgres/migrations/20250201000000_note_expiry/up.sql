@@ -0,0 +1,3 @@
+ALTER TABLE notes ADD COLUMN expires_at BIGINT;
+
+CREATE INDEX idx_notes_expires_at ON notes(expires_at);
@@ -0,0 +1,3 @@
+CREATE INDEX idx_notes_tag ON notes(tag);
+
+DROP INDEX IF EXISTS idx_notes_tag_created_at;
@@ -0,0 +1,3 @@
+CREATE INDEX idx_notes_tag_created_at ON notes(tag, created_at);
+
+DROP INDEX IF EXISTS idx_notes_tag;
@@ -0,0 +1,5 @@
+CREATE INDEX idx_notes_tag_created_at ON notes(tag, created_at);
+
+DROP INDEX IF EXISTS idx_notes_tag_seq;
+DROP INDEX IF EXISTS idx_notes_seq;
+ALTER TABLE notes DROP COLUMN seq;
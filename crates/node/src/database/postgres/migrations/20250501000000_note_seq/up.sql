@@ -0,0 +1,15 @@
+ALTER TABLE notes ADD COLUMN seq BIGINT;
+
+UPDATE notes SET seq = ordered.seq
+FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY created_at, id) AS seq FROM notes) AS ordered
+WHERE notes.id = ordered.id;
+
+CREATE SEQUENCE notes_seq_seq OWNED BY notes.seq;
+SELECT setval('notes_seq_seq', COALESCE(MAX(seq), 0) + 1, false) FROM notes;
+ALTER TABLE notes ALTER COLUMN seq SET DEFAULT nextval('notes_seq_seq');
+ALTER TABLE notes ALTER COLUMN seq SET NOT NULL;
+
+CREATE UNIQUE INDEX idx_notes_seq ON notes(seq);
+CREATE INDEX idx_notes_tag_seq ON notes(tag, seq);
+
+DROP INDEX IF EXISTS idx_notes_tag_created_at;
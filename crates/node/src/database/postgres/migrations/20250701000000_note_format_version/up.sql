@@ -0,0 +1 @@
+ALTER TABLE notes ADD COLUMN format_version SMALLINT NOT NULL DEFAULT 1;
@@ -0,0 +1,6 @@
+CREATE TABLE note_idempotency_keys (
+    key TEXT PRIMARY KEY,
+    note_id BYTEA NOT NULL REFERENCES notes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_note_idempotency_keys_note_id ON note_idempotency_keys(note_id);
@@ -0,0 +1,2 @@
+ALTER TABLE note_idempotency_keys ADD COLUMN duplicate BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE note_idempotency_keys ADD COLUMN created_at BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use miden_objects::utils::Deserializable;
+
+use crate::database::sql::sql_backend;
+use crate::database::{DatabaseBackend, DatabaseConfig, DatabaseError, IdempotencyKey};
+use crate::metrics::MetricsDatabase;
+use crate::types::{NoteId, NoteTag, StoredNote};
+
+mod migrations;
+mod models;
+mod schema;
+
+/// `PostgreSQL` implementation of the database backend
+pub struct PostgresDatabase {
+    pool: deadpool_diesel::postgres::Pool,
+    metrics: MetricsDatabase,
+}
+
+impl PostgresDatabase {
+    /// Open the database, see [`DatabaseBackend::connect`]
+    async fn open(config: DatabaseConfig, metrics: MetricsDatabase) -> Result<Self, DatabaseError> {
+        if config.pool_max_size == 0 {
+            return Err(DatabaseError::Configuration(
+                "Connection pool size must be positive".to_string(),
+            ));
+        }
+
+        let manager = deadpool_diesel::postgres::Manager::new(
+            config.url,
+            deadpool_diesel::postgres::Runtime::Tokio1,
+        );
+        let pool = deadpool_diesel::postgres::Pool::builder(manager)
+            .max_size(config.pool_max_size)
+            .build()
+            .map_err(|e| DatabaseError::Pool(format!("Failed to create connection pool: {e}")))?;
+
+        // Contrary to `SQLite`, the schema is shared by all connections, so migrations are only
+        // applied once
+        let conn = pool
+            .get()
+            .await
+            .map_err(|e| DatabaseError::Connection(format!("Failed to get connection: {e}")))?;
+        conn.interact(migrations::apply_migrations).await??;
+
+        Ok(Self { pool, metrics })
+    }
+
+    /// Remove all stored data
+    ///
+    /// Unlike an in-memory `SQLite` database, a `PostgreSQL` database outlives the test using it.
+    #[cfg(test)]
+    pub(crate) async fn clear(&self) -> Result<(), DatabaseError> {
+        self.transact("clear database", |conn| {
+            diesel::delete(schema::notes::table).execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// Serialize note insertions until the end of the transaction
+///
+/// Sequence numbers are assigned on insertion but become visible on commit. Without serializing
+/// insertions, a note could become visible after a note with a greater sequence number was
+/// fetched, and be skipped by cursors. Reads are not blocked.
+fn lock_notes(conn: &mut PgConnection) -> Result<(), DatabaseError> {
+    diesel::sql_query("LOCK TABLE notes IN EXCLUSIVE MODE").execute(conn)?;
+    Ok(())
+}
+
+sql_backend! {
+    database: PostgresDatabase,
+    connection: PgConnection,
+    name: "PostgreSQL",
+    lock_notes: lock_notes,
+    histogram_query: "SELECT created_at / $1 AS bucket, COUNT(*) AS count FROM notes \
+                      WHERE created_at >= $2 AND created_at < $3 \
+                      GROUP BY bucket ORDER BY bucket ASC",
+}
@@ -0,0 +1 @@
+crate::database::sql::sql_models!(format_version: i16);
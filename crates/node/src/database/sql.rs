@@ -0,0 +1,688 @@
+//! Diesel models and queries shared by the SQL backends
+//!
+//! `SQLite` and `PostgreSQL` run the same queries, only differing in their connection, column
+//! types and insertion locking. Both are expanded from the macros below in each backend module,
+//! which must declare its own `schema` and `models` modules.
+
+/// Declare the note models in a backend `models` module
+///
+/// `format_version` is the Rust type of the `notes.format_version` column.
+macro_rules! sql_models {
+    (format_version: $format_version:ty) => {
+        use chrono::DateTime;
+        use diesel::prelude::*;
+        use diesel::sql_types::BigInt;
+        use miden_objects::utils::{Deserializable, Serializable};
+        use $crate::database::{DatabaseError, details_checksum};
+        use $crate::types::{NoteHeader, StoredNote};
+
+        use super::schema::{note_idempotency_keys, notes};
+
+        #[derive(Queryable, Selectable, Debug, Clone)]
+        #[diesel(table_name = notes)]
+        pub struct Note {
+            pub id: Vec<u8>,
+            pub tag: i64,
+            pub header: Vec<u8>,
+            pub details: Vec<u8>,
+            pub created_at: i64,
+            pub expires_at: Option<i64>,
+            pub acked_at: Option<i64>,
+            pub seq: i64,
+            pub details_checksum: Option<i64>,
+            pub format_version: $format_version,
+        }
+
+        #[derive(Insertable)]
+        #[diesel(table_name = note_idempotency_keys)]
+        pub struct NewIdempotencyKey {
+            pub key: String,
+            pub note_id: Vec<u8>,
+            pub duplicate: bool,
+            pub created_at: i64,
+        }
+
+        /// Row of a note histogram query
+        #[derive(QueryableByName, Debug)]
+        pub struct HistogramBucket {
+            /// Bucket index since the Unix epoch
+            #[diesel(sql_type = BigInt)]
+            pub bucket: i64,
+            #[diesel(sql_type = BigInt)]
+            pub count: i64,
+        }
+
+        #[derive(Insertable)]
+        #[diesel(table_name = notes)]
+        pub struct NewNote {
+            pub id: Vec<u8>,
+            pub tag: i64,
+            pub header: Vec<u8>,
+            pub details: Vec<u8>,
+            pub created_at: i64,
+            pub expires_at: Option<i64>,
+            pub details_checksum: Option<i64>,
+            pub format_version: $format_version,
+        }
+
+        impl From<&StoredNote> for NewNote {
+            fn from(note: &StoredNote) -> Self {
+                Self {
+                    id: note.header.id().as_bytes().to_vec(),
+                    tag: i64::from(note.header.metadata().tag().as_u32()),
+                    header: note.header.to_bytes(),
+                    details: note.details.clone(),
+                    created_at: note.created_at.timestamp_micros(),
+                    expires_at: note.expires_at.map(|expires_at| expires_at.timestamp_micros()),
+                    details_checksum: Some(i64::from(details_checksum(&note.details))),
+                    format_version: note.format_version.into(),
+                }
+            }
+        }
+
+        impl TryFrom<Note> for StoredNote {
+            type Error = DatabaseError;
+
+            fn try_from(note: Note) -> std::result::Result<Self, Self::Error> {
+                let created_at =
+                    DateTime::from_timestamp_micros(note.created_at).ok_or_else(|| {
+                        DatabaseError::Deserialization(format!(
+                            "Invalid timestamp microseconds: {}",
+                            note.created_at
+                        ))
+                    })?;
+
+                let expires_at = note
+                    .expires_at
+                    .map(|expires_at| {
+                        DateTime::from_timestamp_micros(expires_at).ok_or_else(|| {
+                            DatabaseError::Deserialization(format!(
+                                "Invalid expiration timestamp microseconds: {expires_at}"
+                            ))
+                        })
+                    })
+                    .transpose()?;
+
+                let header = NoteHeader::read_from_bytes(&note.header).map_err(|e| {
+                    DatabaseError::Deserialization(format!("Failed to deserialize header: {e}"))
+                })?;
+
+                let seq = u64::try_from(note.seq).map_err(|_| {
+                    DatabaseError::Deserialization(format!("Invalid sequence number: {}", note.seq))
+                })?;
+
+                let format_version = u8::try_from(note.format_version).map_err(|_| {
+                    DatabaseError::Deserialization(format!(
+                        "Invalid format version: {}",
+                        note.format_version
+                    ))
+                })?;
+
+                // Notes stored before checksums were introduced are not verified
+                let checksum = i64::from(details_checksum(&note.details));
+                if note.details_checksum.is_some_and(|stored| stored != checksum) {
+                    return Err(DatabaseError::Corruption(format!(
+                        "Details checksum mismatch of note {seq}"
+                    )));
+                }
+
+                Ok(StoredNote {
+                    header,
+                    details: note.details,
+                    created_at,
+                    expires_at,
+                    seq: Some(seq),
+                    format_version,
+                })
+            }
+        }
+    };
+}
+
+/// Implement [`DatabaseBackend`](crate::database::DatabaseBackend) for a SQL backend
+///
+/// The backend must have `pool` and `metrics` fields, and an inherent `open` constructor used as
+/// [`DatabaseBackend::connect`](crate::database::DatabaseBackend::connect).
+///
+/// - `connection`: diesel connection type of the pool,
+/// - `name`: backend name in error messages,
+/// - `lock_notes`: function called in the transaction before inserting notes,
+/// - `histogram_query`: note histogram query, binding the bucket size, `from` and `to`.
+macro_rules! sql_backend {
+    (
+        database:
+        $database:ident,connection:
+        $connection:ty,name:
+        $name:literal,lock_notes:
+        $lock_notes:path,histogram_query:
+        $histogram_query:literal $(,)?
+    ) => {
+        impl $database {
+            /// Execute a query within a transaction
+            ///
+            /// Writes spanning several statements are made in a single call, an error rolling back
+            /// all of them.
+            async fn transact<R, Q, M>(&self, msg: M, query: Q) -> Result<R, DatabaseError>
+            where
+                Q: Send + FnOnce(&mut $connection) -> Result<R, DatabaseError> + 'static,
+                R: Send + 'static,
+                M: Send + ToString,
+            {
+                let conn = self.pool.get().await.map_err(|e| {
+                    DatabaseError::Connection(format!("Failed to get connection: {e}"))
+                })?;
+
+                conn.interact(|conn| conn.transaction(|conn| query(conn)))
+                    .await
+                    .map_err(|err| {
+                        DatabaseError::QueryExecution(format!(
+                            "Failed to {}: {}",
+                            msg.to_string(),
+                            err
+                        ))
+                    })?
+            }
+
+            /// Execute a query without a transaction
+            async fn query<R, Q, M>(&self, msg: M, query: Q) -> Result<R, DatabaseError>
+            where
+                Q: Send + FnOnce(&mut $connection) -> Result<R, DatabaseError> + 'static,
+                R: Send + 'static,
+                M: Send + ToString,
+            {
+                let conn = self.pool.get().await.map_err(|e| {
+                    DatabaseError::Connection(format!("Failed to get connection: {e}"))
+                })?;
+
+                conn.interact(move |conn| query(conn)).await.map_err(|err| {
+                    DatabaseError::QueryExecution(format!("Failed to {}: {}", msg.to_string(), err))
+                })?
+            }
+
+            /// Fetch notes, optionally excluding acknowledged ones
+            ///
+            /// In descending order, notes stored before `cursor` are fetched, or all notes if `0`.
+            /// Notes expired at `now` are not fetched.
+            async fn fetch(
+                &self,
+                tags: &[NoteTag],
+                cursor: u64,
+                limit: Option<u32>,
+                exclude_acked: bool,
+                descending: bool,
+                now: DateTime<Utc>,
+            ) -> Result<Vec<StoredNote>, DatabaseError> {
+                let timer = self.metrics.db_fetch_notes();
+
+                let cursor_i64: i64 = cursor.try_into().map_err(|_| {
+                    let msg = concat!("Cursor too large for ", $name);
+                    DatabaseError::QueryExecution(msg.to_string())
+                })?;
+
+                let tag_values: Vec<i64> = tags.iter().map(|tag| i64::from(tag.as_u32())).collect();
+                let now = now.timestamp_micros();
+                let notes: Vec<models::Note> = self
+                    .transact("fetch notes", move |conn| {
+                        use schema::notes::dsl::{acked_at, expires_at, notes, seq, tag};
+                        let mut query = notes
+                            .filter(tag.eq_any(tag_values))
+                            .filter(expires_at.is_null().or(expires_at.gt(now)))
+                            .into_boxed();
+                        if !descending {
+                            query = query.filter(seq.gt(cursor_i64)).order(seq.asc());
+                        } else if cursor_i64 != 0 {
+                            query = query.filter(seq.lt(cursor_i64)).order(seq.desc());
+                        } else {
+                            query = query.order(seq.desc());
+                        }
+                        if exclude_acked {
+                            query = query.filter(acked_at.is_null());
+                        }
+                        if let Some(limit) = limit {
+                            query = query.limit(i64::from(limit));
+                        }
+                        let fetched_notes = query.load::<models::Note>(conn)?;
+                        Ok(fetched_notes)
+                    })
+                    .await?;
+
+                let stored_notes = notes
+                    .into_iter()
+                    .map(|note| self.decode(note))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                timer.finish("ok");
+
+                Ok(stored_notes)
+            }
+
+            /// Convert a stored row, counting corrupted notes
+            fn decode(&self, note: models::Note) -> Result<StoredNote, DatabaseError> {
+                StoredNote::try_from(note).map_err(|e| match e {
+                    DatabaseError::Corruption(_) => {
+                        self.metrics.db_corrupted_note();
+                        e
+                    },
+                    e => DatabaseError::Deserialization(format!("Failed to deserialize note: {e}")),
+                })
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl DatabaseBackend for $database {
+            async fn connect(
+                config: DatabaseConfig,
+                metrics: MetricsDatabase,
+            ) -> Result<Self, DatabaseError> {
+                Self::open(config, metrics).await
+            }
+
+            #[tracing::instrument(skip(self), fields(operation = "db.store_note"))]
+            async fn store_note(&self, note: &StoredNote) -> Result<(), DatabaseError> {
+                let timer = self.metrics.db_store_note();
+
+                let new_note = models::NewNote::from(note);
+                self.transact("store note", move |conn| {
+                    $lock_notes(conn)?;
+                    diesel::insert_into(schema::notes::table).values(&new_note).execute(conn)?;
+                    Ok(())
+                })
+                .await?;
+
+                timer.finish("ok");
+                Ok(())
+            }
+
+            #[tracing::instrument(skip(self), fields(operation = "db.store_note"))]
+            async fn store_note_with_metadata(
+                &self,
+                note: &StoredNote,
+                idempotency_key: Option<IdempotencyKey<'_>>,
+            ) -> Result<bool, DatabaseError> {
+                let timer = self.metrics.db_store_note();
+
+                let new_note = models::NewNote::from(note);
+                let new_key = idempotency_key.map(|idempotency_key| {
+                    let key = models::NewIdempotencyKey {
+                        key: idempotency_key.key.to_string(),
+                        note_id: new_note.id.clone(),
+                        duplicate: false,
+                        created_at: new_note.created_at,
+                    };
+                    (key, idempotency_key.expired_before.timestamp_micros())
+                });
+                let inserted = self
+                    .transact("store note", move |conn| {
+                        $lock_notes(conn)?;
+                        let count = diesel::insert_into(schema::notes::table)
+                            .values(&new_note)
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                        let inserted = count > 0;
+                        if let Some((new_key, expired_before)) = new_key {
+                            use schema::note_idempotency_keys::dsl;
+                            diesel::delete(
+                                dsl::note_idempotency_keys
+                                    .filter(dsl::key.eq(&new_key.key))
+                                    .filter(dsl::created_at.lt(expired_before)),
+                            )
+                            .execute(conn)?;
+                            let new_key =
+                                models::NewIdempotencyKey { duplicate: !inserted, ..new_key };
+                            diesel::insert_into(dsl::note_idempotency_keys)
+                                .values(&new_key)
+                                .execute(conn)?;
+                        }
+                        Ok(inserted)
+                    })
+                    .await?;
+
+                timer.finish("ok");
+                Ok(inserted)
+            }
+
+            async fn fetch_idempotency_key(
+                &self,
+                idempotency_key: IdempotencyKey<'_>,
+            ) -> Result<Option<bool>, DatabaseError> {
+                let key = idempotency_key.key.to_string();
+                let expired_before = idempotency_key.expired_before.timestamp_micros();
+                let duplicate: Option<bool> = self
+                    .query("fetch idempotency key", move |conn| {
+                        use schema::note_idempotency_keys::dsl;
+                        let duplicate = dsl::note_idempotency_keys
+                            .filter(dsl::key.eq(key))
+                            .filter(dsl::created_at.ge(expired_before))
+                            .select(dsl::duplicate)
+                            .first::<bool>(conn)
+                            .optional()?;
+                        Ok(duplicate)
+                    })
+                    .await?;
+
+                Ok(duplicate)
+            }
+
+            #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
+            async fn fetch_notes(
+                &self,
+                tags: &[NoteTag],
+                cursor: u64,
+                limit: Option<u32>,
+                now: DateTime<Utc>,
+            ) -> Result<Vec<StoredNote>, DatabaseError> {
+                self.fetch(tags, cursor, limit, false, false, now).await
+            }
+
+            #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
+            async fn fetch_unacked_notes(
+                &self,
+                tags: &[NoteTag],
+                cursor: u64,
+                limit: Option<u32>,
+                now: DateTime<Utc>,
+            ) -> Result<Vec<StoredNote>, DatabaseError> {
+                self.fetch(tags, cursor, limit, true, false, now).await
+            }
+
+            #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
+            async fn fetch_notes_desc(
+                &self,
+                tags: &[NoteTag],
+                cursor: u64,
+                limit: Option<u32>,
+                exclude_acked: bool,
+                now: DateTime<Utc>,
+            ) -> Result<Vec<StoredNote>, DatabaseError> {
+                self.fetch(tags, cursor, limit, exclude_acked, true, now).await
+            }
+
+            async fn count_notes_after(
+                &self,
+                tags: &[NoteTag],
+                cursor: u64,
+                exclude_acked: bool,
+                now: DateTime<Utc>,
+            ) -> Result<u64, DatabaseError> {
+                let cursor_i64: i64 = cursor.try_into().map_err(|_| {
+                    let msg = concat!("Cursor too large for ", $name);
+                    DatabaseError::QueryExecution(msg.to_string())
+                })?;
+
+                let tag_values: Vec<i64> = tags.iter().map(|tag| i64::from(tag.as_u32())).collect();
+                let now = now.timestamp_micros();
+                let count: i64 = self
+                    .query("count notes", move |conn| {
+                        use schema::notes::dsl::{acked_at, expires_at, notes, seq, tag};
+                        let mut query = notes
+                            .filter(tag.eq_any(tag_values))
+                            .filter(seq.gt(cursor_i64))
+                            .filter(expires_at.is_null().or(expires_at.gt(now)))
+                            .into_boxed();
+                        if exclude_acked {
+                            query = query.filter(acked_at.is_null());
+                        }
+                        let count = query.count().get_result(conn)?;
+                        Ok(count)
+                    })
+                    .await?;
+
+                Ok(count.try_into().unwrap_or(0))
+            }
+
+            async fn mark_acked(
+                &self,
+                note_ids: &[NoteId],
+                now: DateTime<Utc>,
+            ) -> Result<u64, DatabaseError> {
+                let ids: Vec<Vec<u8>> =
+                    note_ids.iter().map(|note_id| note_id.as_bytes().to_vec()).collect();
+                let now = now.timestamp_micros();
+
+                let acked_count = self
+                    .transact("acknowledge notes", move |conn| {
+                        use schema::notes::dsl::{acked_at, id, notes};
+                        let count =
+                            diesel::update(notes.filter(id.eq_any(ids)).filter(acked_at.is_null()))
+                                .set(acked_at.eq(now))
+                                .execute(conn)?;
+                        Ok(count)
+                    })
+                    .await?;
+
+                Ok(acked_count.try_into().unwrap_or(0))
+            }
+
+            async fn latest_seq(&self) -> Result<u64, DatabaseError> {
+                let latest: Option<i64> = self
+                    .query("get latest sequence number", |conn| {
+                        use diesel::dsl::max;
+                        use schema::notes::dsl::{notes, seq};
+
+                        let latest = notes.select(max(seq)).first(conn)?;
+                        Ok(latest)
+                    })
+                    .await?;
+
+                Ok(latest.and_then(|latest| latest.try_into().ok()).unwrap_or(0))
+            }
+
+            async fn get_stats(&self) -> Result<(u64, u64), DatabaseError> {
+                let (total_notes, total_tags): (i64, i64) = self
+                    .query("get stats", |conn| {
+                        #[allow(deprecated)]
+                        use diesel::dsl::count_distinct;
+                        use schema::notes::dsl::{notes, tag};
+
+                        let total_notes: i64 = notes.count().get_result(conn)?;
+                        #[allow(deprecated)]
+                        let total_tags: i64 = notes.select(count_distinct(tag)).first(conn)?;
+
+                        Ok((total_notes, total_tags))
+                    })
+                    .await?;
+
+                Ok((total_notes.try_into().unwrap_or(0), total_tags.try_into().unwrap_or(0)))
+            }
+
+            async fn notes_per_tag(
+                &self,
+                limit: u32,
+            ) -> Result<Vec<(NoteTag, u64)>, DatabaseError> {
+                let rows: Vec<(i64, i64)> = self
+                    .query("get notes per tag", move |conn| {
+                        use diesel::dsl::count_star;
+                        use schema::notes::dsl::{notes, tag};
+
+                        let rows = notes
+                            .group_by(tag)
+                            .select((tag, count_star()))
+                            .order((count_star().desc(), tag.asc()))
+                            .limit(i64::from(limit))
+                            .load::<(i64, i64)>(conn)?;
+                        Ok(rows)
+                    })
+                    .await?;
+
+                rows.into_iter()
+                    .map(|(tag, count)| {
+                        let tag = u32::try_from(tag).map_err(|_| {
+                            DatabaseError::Deserialization(format!("Invalid note tag: {tag}"))
+                        })?;
+                        Ok((NoteTag::from(tag), count.try_into().unwrap_or(0)))
+                    })
+                    .collect()
+            }
+
+            async fn cleanup_old_notes(
+                &self,
+                retention_days: u32,
+                now: DateTime<Utc>,
+            ) -> Result<u64, DatabaseError> {
+                let cutoff_date = now - chrono::Duration::days(i64::from(retention_days));
+                let cutoff_timestamp = cutoff_date.timestamp_micros();
+                let now = now.timestamp_micros();
+
+                let deleted_count: i64 = self
+                    .transact("cleanup old notes", move |conn| {
+                        use schema::note_idempotency_keys::dsl::{note_id, note_idempotency_keys};
+                        use schema::notes::dsl::{created_at, expires_at, id, notes};
+                        // Not left to foreign keys, which can be disabled
+                        diesel::delete(
+                            note_idempotency_keys.filter(
+                                note_id.eq_any(
+                                    notes
+                                        .filter(
+                                            created_at.lt(cutoff_timestamp).or(expires_at.le(now)),
+                                        )
+                                        .select(id),
+                                ),
+                            ),
+                        )
+                        .execute(conn)?;
+                        let count = diesel::delete(
+                            notes.filter(created_at.lt(cutoff_timestamp).or(expires_at.le(now))),
+                        )
+                        .execute(conn)?;
+                        Ok(i64::try_from(count).unwrap_or(0))
+                    })
+                    .await?;
+
+                Ok(deleted_count.try_into().unwrap_or(0))
+            }
+
+            async fn get_note(
+                &self,
+                note_id: NoteId,
+                now: DateTime<Utc>,
+            ) -> Result<Option<StoredNote>, DatabaseError> {
+                let now = now.timestamp_micros();
+                let note: Option<models::Note> = self
+                    .query("get note", move |conn| {
+                        use schema::notes::dsl::{expires_at, id, notes};
+                        let note = notes
+                            .filter(id.eq(&note_id.as_bytes()[..]))
+                            .filter(expires_at.is_null().or(expires_at.gt(now)))
+                            .first::<models::Note>(conn)
+                            .optional()?;
+                        Ok(note)
+                    })
+                    .await?;
+
+                note.map(|note| self.decode(note)).transpose()
+            }
+
+            async fn note_exists(&self, note_id: NoteId) -> Result<bool, DatabaseError> {
+                let count: i64 = self
+                    .query("check note existence", move |conn| {
+                        use schema::notes::dsl::{id, notes};
+                        let count = notes
+                            .filter(id.eq(&note_id.as_bytes()[..]))
+                            .count()
+                            .get_result(conn)?;
+                        Ok(count)
+                    })
+                    .await?;
+
+                Ok(count > 0)
+            }
+
+            async fn existing_notes(
+                &self,
+                note_ids: &[NoteId],
+            ) -> Result<Vec<NoteId>, DatabaseError> {
+                let ids: Vec<Vec<u8>> =
+                    note_ids.iter().map(|note_id| note_id.as_bytes().to_vec()).collect();
+
+                let rows: Vec<Vec<u8>> = self
+                    .query("get existing notes", move |conn| {
+                        use schema::notes::dsl::{id, notes};
+                        let rows = notes.filter(id.eq_any(ids)).select(id).load::<Vec<u8>>(conn)?;
+                        Ok(rows)
+                    })
+                    .await?;
+
+                rows.iter()
+                    .map(|bytes| {
+                        NoteId::read_from_bytes(bytes).map_err(|e| {
+                            DatabaseError::Deserialization(format!(
+                                "Failed to deserialize note ID: {e}"
+                            ))
+                        })
+                    })
+                    .collect()
+            }
+
+            async fn list_tags(
+                &self,
+                limit: u32,
+                offset: u32,
+            ) -> Result<Vec<NoteTag>, DatabaseError> {
+                let rows: Vec<i64> = self
+                    .query("list tags", move |conn| {
+                        use schema::notes::dsl::{notes, tag};
+
+                        let rows = notes
+                            .select(tag)
+                            .distinct()
+                            .order(tag.asc())
+                            .limit(i64::from(limit))
+                            .offset(i64::from(offset))
+                            .load::<i64>(conn)?;
+                        Ok(rows)
+                    })
+                    .await?;
+
+                rows.into_iter()
+                    .map(|tag| {
+                        let tag = u32::try_from(tag).map_err(|_| {
+                            DatabaseError::Deserialization(format!("Invalid note tag: {tag}"))
+                        })?;
+                        Ok(NoteTag::from(tag))
+                    })
+                    .collect()
+            }
+
+            async fn note_histogram(
+                &self,
+                bucket_secs: u32,
+                from: DateTime<Utc>,
+                to: DateTime<Utc>,
+            ) -> Result<Vec<(DateTime<Utc>, u64)>, DatabaseError> {
+                let bucket_micros = i64::from(bucket_secs) * 1_000_000;
+                let from = from.timestamp_micros();
+                let to = to.timestamp_micros();
+
+                let rows: Vec<models::HistogramBucket> = self
+                    .query("note histogram", move |conn| {
+                        let rows = diesel::sql_query($histogram_query)
+                            .bind::<BigInt, _>(bucket_micros)
+                            .bind::<BigInt, _>(from)
+                            .bind::<BigInt, _>(to)
+                            .load::<models::HistogramBucket>(conn)?;
+                        Ok(rows)
+                    })
+                    .await?;
+
+                rows.into_iter()
+                    .map(|row| {
+                        let start = row
+                            .bucket
+                            .checked_mul(bucket_micros)
+                            .and_then(DateTime::from_timestamp_micros)
+                            .ok_or_else(|| {
+                                DatabaseError::Deserialization(format!(
+                                    "Invalid histogram bucket: {}",
+                                    row.bucket
+                                ))
+                            })?;
+                        Ok((start, row.count.try_into().unwrap_or(0)))
+                    })
+                    .collect()
+            }
+        }
+    };
+}
+
+pub(crate) use sql_backend;
+pub(crate) use sql_models;
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// `SQLite` connection parameters, applied to each pooled connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqliteConfig {
+    /// Journal mode
+    ///
+    /// `WAL` allows reads concurrent to a write, instead of serializing them.
+    pub journal_mode: SqliteJournalMode,
+    /// Synchronization of writes to disk
+    ///
+    /// `NORMAL` is durable in `WAL` mode except on power loss, with fewer disk syncs than `FULL`.
+    pub synchronous: SqliteSynchronous,
+    /// Time waited for a locked database before failing
+    pub busy_timeout: Duration,
+    /// Enforce foreign key constraints
+    pub foreign_keys: bool,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_secs(30),
+            foreign_keys: true,
+        }
+    }
+}
+
+/// `SQLite` `journal_mode` pragma
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqliteJournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+/// `SQLite` `synchronous` pragma
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqliteSynchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl SqliteJournalMode {
+    /// Pragma value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "DELETE",
+            Self::Truncate => "TRUNCATE",
+            Self::Persist => "PERSIST",
+            Self::Memory => "MEMORY",
+            Self::Wal => "WAL",
+            Self::Off => "OFF",
+        }
+    }
+}
+
+impl SqliteSynchronous {
+    /// Pragma value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Normal => "NORMAL",
+            Self::Full => "FULL",
+            Self::Extra => "EXTRA",
+        }
+    }
+}
+
+impl FromStr for SqliteJournalMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "DELETE" => Ok(Self::Delete),
+            "TRUNCATE" => Ok(Self::Truncate),
+            "PERSIST" => Ok(Self::Persist),
+            "MEMORY" => Ok(Self::Memory),
+            "WAL" => Ok(Self::Wal),
+            "OFF" => Ok(Self::Off),
+            _ => Err(format!("Unknown SQLite journal mode: {s}")),
+        }
+    }
+}
+
+impl FromStr for SqliteSynchronous {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "OFF" => Ok(Self::Off),
+            "NORMAL" => Ok(Self::Normal),
+            "FULL" => Ok(Self::Full),
+            "EXTRA" => Ok(Self::Extra),
+            _ => Err(format!("Unknown SQLite synchronous mode: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for SqliteJournalMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for SqliteSynchronous {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -1,11 +1,11 @@
 //! A minimal connection manager wrapper
 //!
-//! Only required to setup connection parameters, specifically `WAL`.
+//! Only required to setup connection parameters, see [`SqliteConfig`].
 
 use deadpool_sync::InteractError;
 use diesel::prelude::*;
 
-use crate::database::sqlite::migrations;
+use crate::database::sqlite::{SqliteConfig, migrations};
 
 /// Connection manager error types
 #[derive(thiserror::Error, Debug)]
@@ -37,18 +37,20 @@ impl ConnectionManagerError {
 
 /// Create a connection manager with per-connection setup
 ///
-/// Particularly, `foreign_key` checks are enabled and using a write-append-log for journaling.
+/// Particularly, the journal mode, synchronization, busy timeout and `foreign_key` checks are set
+/// from the [`SqliteConfig`].
 pub struct ConnectionManager {
     pub manager: deadpool_diesel::sqlite::Manager,
+    pub config: SqliteConfig,
 }
 
 impl ConnectionManager {
-    pub fn new(database_path: &str) -> Self {
+    pub fn new(database_path: &str, config: SqliteConfig) -> Self {
         let manager = deadpool_diesel::sqlite::Manager::new(
             database_path.to_owned(),
             deadpool_diesel::sqlite::Runtime::Tokio1,
         );
-        Self { manager }
+        Self { manager, config }
     }
 }
 
@@ -59,7 +61,8 @@ impl deadpool::managed::Manager for ConnectionManager {
     async fn create(&self) -> Result<Self::Type, Self::Error> {
         let conn = self.manager.create().await.map_err(ConnectionManagerError::ConnectionCreate)?;
 
-        conn.interact(configure_connection_on_creation)
+        let config = self.config.clone();
+        conn.interact(move |conn| configure_connection_on_creation(conn, &config))
             .await
             .map_err(|e| ConnectionManagerError::interact("Connection setup", &e))??;
         Ok(conn)
@@ -79,21 +82,25 @@ impl deadpool::managed::Manager for ConnectionManager {
 
 pub fn configure_connection_on_creation(
     conn: &mut SqliteConnection,
+    config: &SqliteConfig,
 ) -> Result<(), ConnectionManagerError> {
-    // Enable the WAL mode. This allows concurrent reads while the transaction is being written,
-    // this is required for proper synchronization of the servers in-memory and on-disk
-    // representations (see [State::apply_block])
-    diesel::sql_query("PRAGMA journal_mode=WAL")
+    // Set the journal mode. `WAL` allows concurrent reads while a transaction is being written
+    diesel::sql_query(format!("PRAGMA journal_mode={}", config.journal_mode))
+        .execute(conn)
+        .map_err(ConnectionManagerError::ConnectionParamSetup)?;
+
+    diesel::sql_query(format!("PRAGMA synchronous={}", config.synchronous))
         .execute(conn)
         .map_err(ConnectionManagerError::ConnectionParamSetup)?;
 
     // Enable foreign key checks.
-    diesel::sql_query("PRAGMA foreign_keys=ON")
+    let foreign_keys = if config.foreign_keys { "ON" } else { "OFF" };
+    diesel::sql_query(format!("PRAGMA foreign_keys={foreign_keys}"))
         .execute(conn)
         .map_err(ConnectionManagerError::ConnectionParamSetup)?;
 
-    // Set busy timeout to handle concurrent access (30 seconds)
-    diesel::sql_query("PRAGMA busy_timeout=30000")
+    // Set busy timeout to handle concurrent access
+    diesel::sql_query(format!("PRAGMA busy_timeout={}", config.busy_timeout.as_millis()))
         .execute(conn)
         .map_err(ConnectionManagerError::ConnectionParamSetup)?;
 
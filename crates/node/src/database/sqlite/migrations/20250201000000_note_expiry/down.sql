@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_notes_expires_at;
+ALTER TABLE notes DROP COLUMN expires_at;
@@ -0,0 +1 @@
+ALTER TABLE notes ADD COLUMN acked_at INTEGER;
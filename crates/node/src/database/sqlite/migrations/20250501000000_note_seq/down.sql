@@ -0,0 +1,19 @@
+CREATE TABLE notes_created_at (
+    id BLOB PRIMARY KEY,
+    tag INTEGER NOT NULL,
+    header BLOB NOT NULL,
+    details BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER,
+    acked_at INTEGER
+) STRICT;
+
+INSERT INTO notes_created_at (id, tag, header, details, created_at, expires_at, acked_at)
+SELECT id, tag, header, details, created_at, expires_at, acked_at FROM notes;
+
+DROP TABLE notes;
+ALTER TABLE notes_created_at RENAME TO notes;
+
+CREATE INDEX idx_notes_tag_created_at ON notes(tag, created_at);
+CREATE INDEX idx_notes_created_at ON notes(created_at);
+CREATE INDEX idx_notes_expires_at ON notes(expires_at);
@@ -0,0 +1,20 @@
+CREATE TABLE notes_seq (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id BLOB NOT NULL UNIQUE,
+    tag INTEGER NOT NULL,
+    header BLOB NOT NULL,
+    details BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER,
+    acked_at INTEGER
+) STRICT;
+
+INSERT INTO notes_seq (id, tag, header, details, created_at, expires_at, acked_at)
+SELECT id, tag, header, details, created_at, expires_at, acked_at FROM notes ORDER BY created_at, id;
+
+DROP TABLE notes;
+ALTER TABLE notes_seq RENAME TO notes;
+
+CREATE INDEX idx_notes_tag_seq ON notes(tag, seq);
+CREATE INDEX idx_notes_created_at ON notes(created_at);
+CREATE INDEX idx_notes_expires_at ON notes(expires_at);
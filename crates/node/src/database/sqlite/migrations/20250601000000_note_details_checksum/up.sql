@@ -0,0 +1 @@
+ALTER TABLE notes ADD COLUMN details_checksum INTEGER;
@@ -0,0 +1 @@
+ALTER TABLE notes ADD COLUMN format_version INTEGER NOT NULL DEFAULT 1;
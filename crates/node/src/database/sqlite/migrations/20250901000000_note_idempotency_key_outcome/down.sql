@@ -0,0 +1,2 @@
+ALTER TABLE note_idempotency_keys DROP COLUMN created_at;
+ALTER TABLE note_idempotency_keys DROP COLUMN duplicate;
@@ -0,0 +1,2 @@
+ALTER TABLE note_idempotency_keys ADD COLUMN duplicate INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE note_idempotency_keys ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
@@ -1,17 +1,21 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use miden_objects::utils::Deserializable;
 
-use crate::database::{DatabaseBackend, DatabaseConfig, DatabaseError};
+use crate::database::sql::sql_backend;
+use crate::database::{DatabaseBackend, DatabaseConfig, DatabaseError, IdempotencyKey};
 use crate::metrics::MetricsDatabase;
 use crate::types::{NoteId, NoteTag, StoredNote};
 
+mod config;
 mod connection_manager;
 mod migrations;
 mod models;
 mod schema;
 
+pub use config::{SqliteConfig, SqliteJournalMode, SqliteSynchronous};
 use connection_manager::ConnectionManager;
-use models::{NewNote, Note};
 
 /// `SQLite` implementation of the database backend
 pub struct SqliteDatabase {
@@ -20,163 +24,142 @@ pub struct SqliteDatabase {
 }
 
 impl SqliteDatabase {
-    /// Execute a query within a transaction
-    async fn transact<R, Q, M>(&self, msg: M, query: Q) -> Result<R, DatabaseError>
-    where
-        Q: Send + FnOnce(&mut SqliteConnection) -> Result<R, DatabaseError> + 'static,
-        R: Send + 'static,
-        M: Send + ToString,
-    {
-        let conn = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| DatabaseError::Connection(format!("Failed to get connection: {e}")))?;
-
-        conn.interact(|conn| conn.transaction(|conn| query(conn)))
-            .await
-            .map_err(|err| {
-                DatabaseError::QueryExecution(format!("Failed to {}: {}", msg.to_string(), err))
-            })?
-    }
-
-    /// Execute a query without a transaction
-    async fn query<R, Q, M>(&self, msg: M, query: Q) -> Result<R, DatabaseError>
-    where
-        Q: Send + FnOnce(&mut SqliteConnection) -> Result<R, DatabaseError> + 'static,
-        R: Send + 'static,
-        M: Send + ToString,
-    {
-        let conn = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| DatabaseError::Connection(format!("Failed to get connection: {e}")))?;
-
-        conn.interact(move |conn| query(conn)).await.map_err(|err| {
-            DatabaseError::QueryExecution(format!("Failed to {}: {}", msg.to_string(), err))
-        })?
-    }
-}
+    /// Open the database, see [`DatabaseBackend::connect`]
+    async fn open(config: DatabaseConfig, metrics: MetricsDatabase) -> Result<Self, DatabaseError> {
+        if config.pool_max_size == 0 {
+            return Err(DatabaseError::Configuration(
+                "Connection pool size must be positive".to_string(),
+            ));
+        }
 
-#[async_trait::async_trait]
-impl DatabaseBackend for SqliteDatabase {
-    async fn connect(
-        config: DatabaseConfig,
-        metrics: MetricsDatabase,
-    ) -> Result<Self, DatabaseError> {
         if !std::path::Path::new(&config.url).exists() && !config.url.contains(":memory:") {
             std::fs::File::create(&config.url).map_err(|e| {
                 DatabaseError::Configuration(format!("Failed to create database file: {e}"))
             })?;
         }
 
-        let manager = ConnectionManager::new(&config.url);
+        let manager = ConnectionManager::new(&config.url, config.sqlite);
         let pool = deadpool_diesel::Pool::builder(manager)
-            .max_size(16)
+            .max_size(config.pool_max_size)
             .build()
-            .map_err(|e| DatabaseError::Pool(format!("Failed to create connection pool: {e}")))?;
+            .map_err(|e| {
+            DatabaseError::Pool(format!("Failed to create connection pool: {e}"))
+        })?;
 
         Ok(Self { pool, metrics })
     }
+}
 
-    #[tracing::instrument(skip(self), fields(operation = "db.store_note"))]
-    async fn store_note(&self, note: &StoredNote) -> Result<(), DatabaseError> {
-        let timer = self.metrics.db_store_note();
-
-        let new_note = NewNote::from(note);
-        self.transact("store note", move |conn| {
-            diesel::insert_into(schema::notes::table).values(&new_note).execute(conn)?;
-            Ok(())
-        })
-        .await?;
-
-        timer.finish("ok");
-        Ok(())
-    }
-
-    #[tracing::instrument(skip(self), fields(operation = "db.fetch_notes"))]
-    async fn fetch_notes(
-        &self,
-        tag: NoteTag,
-        cursor: u64,
-    ) -> Result<Vec<StoredNote>, DatabaseError> {
-        let timer = self.metrics.db_fetch_notes();
-
-        let cursor_i64: i64 = cursor.try_into().map_err(|_| {
-            DatabaseError::QueryExecution("Cursor too large for SQLite".to_string())
-        })?;
+/// Nothing to lock, `SQLite` serializes writes on its database lock
+#[allow(clippy::unnecessary_wraps)]
+fn lock_notes(_conn: &mut SqliteConnection) -> Result<(), DatabaseError> {
+    Ok(())
+}
 
-        let tag_value = i64::from(tag.as_u32());
-        let notes: Vec<Note> = self
-            .transact("fetch notes", move |conn| {
-                use schema::notes::dsl::{created_at, notes, tag};
-                let fetched_notes = notes
-                    .filter(tag.eq(tag_value))
-                    .filter(created_at.gt(cursor_i64))
-                    .order(created_at.asc())
-                    .load::<Note>(conn)?;
-                Ok(fetched_notes)
-            })
-            .await?;
+sql_backend! {
+    database: SqliteDatabase,
+    connection: SqliteConnection,
+    name: "SQLite",
+    lock_notes: lock_notes,
+    histogram_query: "SELECT created_at / ? AS bucket, COUNT(*) AS count FROM notes \
+                      WHERE created_at >= ? AND created_at < ? \
+                      GROUP BY bucket ORDER BY bucket ASC",
+}
 
-        let mut stored_notes = Vec::new();
-        for note in notes {
-            let stored_note = StoredNote::try_from(note).map_err(|e| {
-                DatabaseError::Deserialization(format!("Failed to deserialize note: {e}"))
-            })?;
-            stored_notes.push(stored_note);
-        }
+#[cfg(test)]
+mod tests {
+    use diesel::sql_types::Text;
 
-        timer.finish("ok");
+    use super::*;
+    use crate::metrics::Metrics;
+    use crate::test_utils::{TestMetrics, test_note_header};
 
-        Ok(stored_notes)
+    #[derive(QueryableByName)]
+    struct QueryPlanRow {
+        #[diesel(sql_type = Text)]
+        detail: String,
     }
 
-    async fn get_stats(&self) -> Result<(u64, u64), DatabaseError> {
-        let (total_notes, total_tags): (i64, i64) = self
-            .query("get stats", |conn| {
-                #[allow(deprecated)]
-                use diesel::dsl::count_distinct;
-                use schema::notes::dsl::{notes, tag};
-
-                let total_notes: i64 = notes.count().get_result(conn)?;
-                #[allow(deprecated)]
-                let total_tags: i64 = notes.select(count_distinct(tag)).first(conn)?;
-
-                Ok((total_notes, total_tags))
+    #[tokio::test]
+    async fn test_fetch_notes_query_plan() {
+        let db = SqliteDatabase::connect(DatabaseConfig::default(), Metrics::default().db)
+            .await
+            .unwrap();
+
+        // Same filters and ordering as `SqliteDatabase::fetch`
+        let plan = db
+            .query("explain fetch notes", |conn| {
+                let rows = diesel::sql_query(
+                    "EXPLAIN QUERY PLAN SELECT * FROM notes \
+                     WHERE tag IN (1, 2) AND seq > 0 \
+                     AND (expires_at IS NULL OR expires_at > 0) \
+                     ORDER BY seq ASC LIMIT 10",
+                )
+                .load::<QueryPlanRow>(conn)?;
+                Ok(rows)
             })
-            .await?;
+            .await
+            .unwrap();
 
-        Ok((total_notes.try_into().unwrap_or(0), total_tags.try_into().unwrap_or(0)))
+        assert!(
+            plan.iter().any(|row| row.detail.contains("USING INDEX idx_notes_tag_seq")),
+            "{:?}",
+            plan.iter().map(|row| &row.detail).collect::<Vec<_>>()
+        );
     }
 
-    async fn cleanup_old_notes(&self, retention_days: u32) -> Result<u64, DatabaseError> {
-        let cutoff_date = Utc::now() - chrono::Duration::days(i64::from(retention_days));
-        let cutoff_timestamp = cutoff_date.timestamp_micros();
-
-        let deleted_count: i64 = self
-            .transact("cleanup old notes", move |conn| {
-                use schema::notes::dsl::{created_at, notes};
-                let count =
-                    diesel::delete(notes.filter(created_at.lt(cutoff_timestamp))).execute(conn)?;
-                Ok(i64::try_from(count).unwrap_or(0))
-            })
-            .await?;
+    #[tokio::test]
+    async fn test_fetch_notes_corrupted_details() {
+        let metrics = TestMetrics::new();
+        let db = SqliteDatabase::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+            .await
+            .unwrap();
+
+        let header = test_note_header();
+        let tag = header.metadata().tag();
+        let note = StoredNote {
+            header,
+            details: vec![1, 2, 3, 4],
+            created_at: Utc::now(),
+            expires_at: None,
+            seq: None,
+            format_version: 1,
+        };
+        db.store_note(&note).await.unwrap();
+        assert_eq!(db.fetch_notes(&[tag], 0, None, Utc::now()).await.unwrap().len(), 1);
+
+        // Corrupt the stored details
+        db.query("corrupt note", |conn| {
+            diesel::sql_query("UPDATE notes SET details = x'01020305'").execute(conn)?;
+            Ok(())
+        })
+        .await
+        .unwrap();
 
-        Ok(deleted_count.try_into().unwrap_or(0))
+        let error = db.fetch_notes(&[tag], 0, None, Utc::now()).await.unwrap_err();
+        assert!(matches!(error, DatabaseError::Corruption(_)), "{error:?}");
+        assert_eq!(metrics.counter("db_corrupted_notes_count"), 1);
     }
 
-    async fn note_exists(&self, note_id: NoteId) -> Result<bool, DatabaseError> {
-        let count: i64 = self
-            .query("check note existence", move |conn| {
-                use schema::notes::dsl::{id, notes};
-                let count =
-                    notes.filter(id.eq(&note_id.as_bytes()[..])).count().get_result(conn)?;
-                Ok(count)
-            })
-            .await?;
+    #[tokio::test]
+    async fn test_pool_max_size() {
+        let config = DatabaseConfig { pool_max_size: 2, ..Default::default() };
+        let db = std::sync::Arc::new(
+            SqliteDatabase::connect(config, Metrics::default().db).await.unwrap(),
+        );
+
+        // Queries beyond the pool size wait for a connection
+        let mut queries = tokio::task::JoinSet::new();
+        for _ in 0..8 {
+            let db = db.clone();
+            queries.spawn(async move { db.get_stats().await });
+        }
+        while let Some(result) = queries.join_next().await {
+            assert_eq!(result.unwrap().unwrap(), (0, 0));
+        }
 
-        Ok(count > 0)
+        let config = DatabaseConfig { pool_max_size: 0, ..Default::default() };
+        let result = SqliteDatabase::connect(config, Metrics::default().db).await;
+        assert!(matches!(result, Err(DatabaseError::Configuration(_))));
     }
 }
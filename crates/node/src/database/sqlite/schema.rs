@@ -1,11 +1,25 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
-    notes (id) {
+    notes (seq) {
         id -> Binary,
         tag -> BigInt,
         header -> Binary,
         details -> Binary,
         created_at -> BigInt,
+        expires_at -> Nullable<BigInt>,
+        acked_at -> Nullable<BigInt>,
+        seq -> BigInt,
+        details_checksum -> Nullable<BigInt>,
+        format_version -> Integer,
+    }
+}
+
+diesel::table! {
+    note_idempotency_keys (key) {
+        key -> Text,
+        note_id -> Binary,
+        duplicate -> Bool,
+        created_at -> BigInt,
     }
 }
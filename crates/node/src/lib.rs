@@ -13,7 +13,8 @@
 //!
 //! ## Database
 //! Notes are stored in a database, implementing the
-//! [`Database`](`crate::database::DatabaseBackend`). A SQLite-based  implementation is provided.
+//! [`Database`](`crate::database::DatabaseBackend`). A SQLite-based implementation is provided, as
+//! well as a PostgreSQL-based one behind the `postgres` feature.
 //!
 //! ### Maintenance
 //! A periodic task [`DatabaseMaintenance`](`crate::database::DatabaseMaintenance`) takes care of
@@ -24,9 +25,13 @@
 //! While metrics provide insights into general requests stats, traces can provide insights into
 //! specific requests.
 //! Metrics and traces can be exported following using the [OpenTelemetry](https://opentelemetry.io) framework.
+//! Metrics can also be scraped by Prometheus, see
+//! [`PrometheusServer`](`crate::node::prometheus::PrometheusServer`).
 
 #![deny(missing_docs)]
 
+/// Time source
+pub mod clock;
 /// Database
 pub mod database;
 /// Error management
@@ -3,7 +3,6 @@ use std::str::FromStr;
 use anyhow::Result;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use opentelemetry_sdk::trace::SpanExporter;
 use tracing::subscriber::Subscriber;
@@ -66,6 +65,14 @@ impl OpenTelemetry {
     pub fn is_enabled(&self) -> bool {
         matches!(self, OpenTelemetry::Enabled { .. })
     }
+
+    /// OpenTelemetry endpoint, if enabled
+    pub fn endpoint(&self) -> Option<&str> {
+        match self {
+            OpenTelemetry::Enabled { endpoint } => Some(endpoint),
+            OpenTelemetry::Disabled => None,
+        }
+    }
 }
 
 /// Initializes tracing to stdout and optionally an open-telemetry exporter.
@@ -78,9 +85,6 @@ impl OpenTelemetry {
 pub fn setup_tracing(cfg: TracingConfig) -> Result<()> {
     if cfg.otel.is_enabled() {
         opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
-
-        // Setup metrics export if OTEL is enabled
-        setup_metrics_export(&cfg.otel)?;
     }
 
     // Note: open-telemetry requires a tokio-runtime, so this _must_ be lazily evaluated (aka not
@@ -109,30 +113,6 @@ pub fn setup_tracing(cfg: TracingConfig) -> Result<()> {
     tracing::subscriber::set_global_default(subscriber).map_err(Into::into)
 }
 
-/// Setup OpenTelemetry metrics export using the proper SDK API
-fn setup_metrics_export(otel_cfg: &OpenTelemetry) -> Result<()> {
-    if let OpenTelemetry::Enabled { endpoint } = otel_cfg {
-        // Configure OTLP metrics pipeline
-        let exporter = opentelemetry_otlp::MetricExporter::builder()
-            .with_tonic()
-            .with_endpoint(endpoint)
-            .build()?;
-
-        let provider = SdkMeterProvider::builder()
-            .with_reader(
-                PeriodicReader::builder(exporter)
-                    .with_interval(std::time::Duration::from_secs(5)) // Push interval
-                    .build(),
-            )
-            .build();
-
-        // Set the meter provider globally
-        opentelemetry::global::set_meter_provider(provider);
-    }
-
-    Ok(())
-}
-
 /// Initializes tracing to a test exporter.
 ///
 /// Allows trace content to be inspected via the returned receiver.
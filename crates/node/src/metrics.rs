@@ -1,5 +1,36 @@
 use opentelemetry::KeyValue;
-use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+
+use crate::node::prometheus::PrometheusReader;
+
+/// Metrics configuration
+///
+/// Both exports, OTLP and Prometheus, can be enabled at the same time.
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    /// OpenTelemetry (OTLP) endpoint metrics are pushed to
+    pub otlp_endpoint: Option<String>,
+    /// Port of the HTTP endpoint serving metrics in the Prometheus text format
+    pub prometheus_port: Option<u16>,
+    /// Bucket boundaries of size histograms, in bytes
+    pub size_buckets: Vec<f64>,
+    /// Bucket boundaries of duration histograms, in seconds
+    pub duration_buckets: Vec<f64>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            prometheus_port: None,
+            // Mostly a few KB, with rare MB outliers
+            size_buckets: vec![256.0, 1_024.0, 4_096.0, 16_384.0, 65_536.0, 262_144.0, 1_048_576.0],
+            duration_buckets: vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0],
+        }
+    }
+}
 
 /// Transport metrics using OpenTelemetry metrics
 ///
@@ -8,6 +39,8 @@ use opentelemetry::metrics::{Counter, Histogram, Meter};
 pub struct Metrics {
     /// [`crate::node::grpc::GrpcServer`] metrics
     pub grpc: MetricsGrpc,
+    /// Note streaming metrics
+    pub streaming: MetricsStreaming,
     /// [`crate::database::Database`] metrics
     pub db: MetricsDatabase,
 }
@@ -20,11 +53,25 @@ pub struct MetricsGrpc {
     send_note_count: Counter<u64>,
     send_note_duration: Histogram<f64>,
     send_note_note_size: Histogram<u64>,
+    send_note_duplicate_count: Counter<u64>,
+    send_note_rejected_count: Counter<u64>,
+    send_note_near_limit_count: Counter<u64>,
     // fetch_notes()
     fetch_notes_count: Counter<u64>,
     fetch_notes_duration: Histogram<f64>,
     fetch_notes_replied_notes_number: Histogram<u64>,
     fetch_notes_replied_notes_size: Histogram<u64>,
+    fetch_notes_cache_hit_count: Counter<u64>,
+    fetch_notes_limit_clamped_count: Counter<u64>,
+}
+
+/// Note streaming metrics
+#[derive(Debug, Clone)]
+pub struct MetricsStreaming {
+    // -- Streaming
+    active_subscriptions: Gauge<u64>,
+    notes_delivered_count: Counter<u64>,
+    subs_dropped_count: Counter<u64>,
 }
 
 /// [`crate::database::Database`] metrics
@@ -37,23 +84,27 @@ pub struct MetricsDatabase {
     // fetch_notes()
     fetch_notes_count: Counter<u64>,
     fetch_notes_duration: Histogram<f64>,
+    corrupted_notes_count: Counter<u64>,
     // Maintenance
     maintenance_cleanup_notes_count: Counter<u64>,
     maintenance_cleanup_notes_duration: Histogram<f64>,
+    maintenance_notes_deleted_count: Counter<u64>,
+    maintenance_last_run_timestamp: Gauge<u64>,
 }
 
 impl Metrics {
     /// Create a new instance of `Metrics`
-    pub fn new(meter: &Meter) -> Self {
-        let grpc = MetricsGrpc::new(meter);
-        let db = MetricsDatabase::new(meter);
-        Self { grpc, db }
+    pub fn new(meter: &Meter, config: &MetricsConfig) -> Self {
+        let grpc = MetricsGrpc::new(meter, config);
+        let streaming = MetricsStreaming::new(meter);
+        let db = MetricsDatabase::new(meter, config);
+        Self { grpc, streaming, db }
     }
 }
 
 impl MetricsGrpc {
     /// Create a new instance of `MetricsGrpc`
-    pub fn new(meter: &Meter) -> Self {
+    pub fn new(meter: &Meter, config: &MetricsConfig) -> Self {
         let send_note_count = meter
             .u64_counter("grpc_send_note_count")
             .with_description("Total number of gRPC send_note() requests")
@@ -63,12 +114,31 @@ impl MetricsGrpc {
             .f64_histogram("grpc_send_note_duration")
             .with_description("Duration of gRPC send_note() requests in seconds")
             .with_unit("s")
+            .with_boundaries(config.duration_buckets.clone())
             .build();
 
         let send_note_note_size = meter
             .u64_histogram("grpc_send_note_note_size")
             .with_description("Size of incoming note in send_note() requests in bytes")
             .with_unit("B")
+            .with_boundaries(config.size_buckets.clone())
+            .build();
+
+        let send_note_duplicate_count = meter
+            .u64_counter("grpc_send_note_duplicate_count")
+            .with_description("Total number of gRPC send_note() requests of already stored notes")
+            .build();
+
+        let send_note_rejected_count = meter
+            .u64_counter("grpc_send_note_rejected_count")
+            .with_description("Total number of rejected gRPC send_note() requests, by reason")
+            .build();
+
+        let send_note_near_limit_count = meter
+            .u64_counter("grpc_send_note_near_limit_count")
+            .with_description(
+                "Total number of gRPC send_note() requests of notes above the size warning threshold",
+            )
             .build();
 
         let fetch_notes_count = meter
@@ -80,6 +150,7 @@ impl MetricsGrpc {
             .f64_histogram("grpc_fetch_notes_duration")
             .with_description("Duration of gRPC fetch_notes() requests in seconds")
             .with_unit("s")
+            .with_boundaries(config.duration_buckets.clone())
             .build();
 
         let fetch_notes_replied_notes_number = meter
@@ -91,16 +162,32 @@ impl MetricsGrpc {
             .u64_histogram("grpc_fetch_notes_replied_notes_size")
             .with_description("Total size of replied notes per gRPC fetch_notes() request in bytes")
             .with_unit("B")
+            .with_boundaries(config.size_buckets.clone())
+            .build();
+
+        let fetch_notes_cache_hit_count = meter
+            .u64_counter("grpc_fetch_notes_cache_hit_count")
+            .with_description("Total number of gRPC fetch_notes() requests served from the cache")
+            .build();
+
+        let fetch_notes_limit_clamped_count = meter
+            .u64_counter("grpc_fetch_notes_limit_clamped_count")
+            .with_description("Total number of gRPC fetch_notes() requests with a clamped limit")
             .build();
 
         Self {
             send_note_count,
             send_note_duration,
             send_note_note_size,
+            send_note_duplicate_count,
+            send_note_rejected_count,
+            send_note_near_limit_count,
             fetch_notes_count,
             fetch_notes_duration,
             fetch_notes_replied_notes_number,
             fetch_notes_replied_notes_size,
+            fetch_notes_cache_hit_count,
+            fetch_notes_limit_clamped_count,
         }
     }
 
@@ -118,6 +205,38 @@ impl MetricsGrpc {
         request_count_measure(operation, counter, histogram)
     }
 
+    /// Record a send-note request with an already stored note
+    pub fn grpc_send_note_duplicate(&self) {
+        let operation = "grpc.send_note.request";
+
+        self.send_note_duplicate_count
+            .add(1, &[KeyValue::new("operation", operation.to_string())]);
+    }
+
+    /// Record a rejected send-note request
+    ///
+    /// `reason` is one of `too_large`, `invalid_header`, `tag_not_allowed`, `rate_limited`,
+    /// `invalid_expiration` or `invalid_format_version`.
+    pub fn grpc_send_note_rejected(&self, reason: &str) {
+        let operation = "grpc.send_note.request";
+
+        self.send_note_rejected_count.add(
+            1,
+            &[
+                KeyValue::new("operation", operation.to_string()),
+                KeyValue::new("reason", reason.to_string()),
+            ],
+        );
+    }
+
+    /// Record a send-note request of a note above the size warning threshold
+    pub fn grpc_send_note_near_limit(&self) {
+        let operation = "grpc.send_note.request";
+
+        self.send_note_near_limit_count
+            .add(1, &[KeyValue::new("operation", operation.to_string())]);
+    }
+
     /// Measure a fetch-notes request
     ///
     /// Increases the request counter and measures request duration.
@@ -140,11 +259,84 @@ impl MetricsGrpc {
         self.fetch_notes_replied_notes_size
             .record(size_b, &[KeyValue::new("operation", operation.to_string())]);
     }
+
+    /// Record a fetch-notes request served from the cache
+    pub fn grpc_fetch_notes_cache_hit(&self) {
+        let operation = "grpc.fetch_notes";
+
+        self.fetch_notes_cache_hit_count
+            .add(1, &[KeyValue::new("operation", operation.to_string())]);
+    }
+
+    /// Record a fetch-notes request with a limit clamped to the server maximum
+    pub fn grpc_fetch_notes_limit_clamped(&self) {
+        let operation = "grpc.fetch_notes";
+
+        self.fetch_notes_limit_clamped_count
+            .add(1, &[KeyValue::new("operation", operation.to_string())]);
+    }
+}
+
+impl MetricsStreaming {
+    /// Create a new instance of `MetricsStreaming`
+    pub fn new(meter: &Meter) -> Self {
+        let active_subscriptions = meter
+            .u64_gauge("streaming_active_subscriptions")
+            .with_description("Number of active stream_notes() subscriptions")
+            .build();
+
+        let notes_delivered_count = meter
+            .u64_counter("streaming_notes_delivered_count")
+            .with_description("Total number of notes delivered to stream_notes() subscribers")
+            .build();
+
+        let subs_dropped_count = meter
+            .u64_counter("streaming_subs_dropped_count")
+            .with_description("Total number of stream_notes() subscribers dropped, by reason")
+            .build();
+
+        Self {
+            active_subscriptions,
+            notes_delivered_count,
+            subs_dropped_count,
+        }
+    }
+
+    /// Record the number of active subscriptions
+    pub fn streaming_active_subscriptions(&self, count: u64) {
+        let operation = "streaming";
+
+        self.active_subscriptions
+            .record(count, &[KeyValue::new("operation", operation.to_string())]);
+    }
+
+    /// Record notes delivered to a subscriber
+    pub fn streaming_notes_delivered(&self, count: u64) {
+        let operation = "streaming";
+
+        self.notes_delivered_count
+            .add(count, &[KeyValue::new("operation", operation.to_string())]);
+    }
+
+    /// Record a dropped subscriber
+    ///
+    /// `reason` is `slow` for subscribers not keeping up with updates, `closed` for gone ones.
+    pub fn streaming_sub_dropped(&self, reason: &str) {
+        let operation = "streaming";
+
+        self.subs_dropped_count.add(
+            1,
+            &[
+                KeyValue::new("operation", operation.to_string()),
+                KeyValue::new("reason", reason.to_string()),
+            ],
+        );
+    }
 }
 
 impl MetricsDatabase {
     /// Create a new instance of `MetricsDatabase`
-    pub fn new(meter: &Meter) -> Self {
+    pub fn new(meter: &Meter, config: &MetricsConfig) -> Self {
         let store_note_count = meter
             .u64_counter("db_store_note_count")
             .with_description("Total number of DB store_note() requests")
@@ -154,6 +346,7 @@ impl MetricsDatabase {
             .f64_histogram("db_store_note_duration")
             .with_description("Duration of DB store_note() requests in seconds")
             .with_unit("s")
+            .with_boundaries(config.duration_buckets.clone())
             .build();
 
         let fetch_notes_count = meter
@@ -165,6 +358,12 @@ impl MetricsDatabase {
             .f64_histogram("db_fetch_notes_duration")
             .with_description("Duration of dB fetch_notes() requests in seconds")
             .with_unit("s")
+            .with_boundaries(config.duration_buckets.clone())
+            .build();
+
+        let corrupted_notes_count = meter
+            .u64_counter("db_corrupted_notes_count")
+            .with_description("Total number of DB notes failing their details checksum")
             .build();
 
         let maintenance_cleanup_notes_count = meter
@@ -176,6 +375,18 @@ impl MetricsDatabase {
             .f64_histogram("db_maintenance_cleanup_notes_duration")
             .with_description("Duration of DB maintenance cleanup_old_notes() requests in seconds")
             .with_unit("s")
+            .with_boundaries(config.duration_buckets.clone())
+            .build();
+
+        let maintenance_notes_deleted_count = meter
+            .u64_counter("db_maintenance_notes_deleted_count")
+            .with_description("Total number of notes deleted by DB maintenance")
+            .build();
+
+        let maintenance_last_run_timestamp = meter
+            .u64_gauge("db_maintenance_last_run_timestamp")
+            .with_description("Unix timestamp of the last successful DB maintenance run in seconds")
+            .with_unit("s")
             .build();
 
         Self {
@@ -183,8 +394,11 @@ impl MetricsDatabase {
             store_note_duration,
             fetch_notes_count,
             fetch_notes_duration,
+            corrupted_notes_count,
             maintenance_cleanup_notes_count,
             maintenance_cleanup_notes_duration,
+            maintenance_notes_deleted_count,
+            maintenance_last_run_timestamp,
         }
     }
 
@@ -210,6 +424,14 @@ impl MetricsDatabase {
         request_count_measure(operation, counter, histogram)
     }
 
+    /// Record a fetched DB note failing its details checksum
+    pub fn db_corrupted_note(&self) {
+        let operation = "db.fetch_notes";
+
+        self.corrupted_notes_count
+            .add(1, &[KeyValue::new("operation", operation.to_string())]);
+    }
+
     /// Measure a DB maintenance cleanup-old-notes procedure
     ///
     /// Increases the request counter and measures request duration.
@@ -220,6 +442,18 @@ impl MetricsDatabase {
 
         request_count_measure(operation, counter, histogram)
     }
+
+    /// Record a successful DB maintenance cleanup-old-notes procedure
+    ///
+    /// Increases the deleted notes counter and sets the last run timestamp.
+    pub fn db_maintenance_cleanup_notes_done(&self, deleted: u64, timestamp_s: u64) {
+        let operation = "db.maintenance.cleanup_old_notes";
+
+        self.maintenance_notes_deleted_count
+            .add(deleted, &[KeyValue::new("operation", operation.to_string())]);
+        self.maintenance_last_run_timestamp
+            .record(timestamp_s, &[KeyValue::new("operation", operation.to_string())]);
+    }
 }
 
 /// Measure a request
@@ -242,10 +476,41 @@ fn request_count_measure<'a>(
     }
 }
 
+impl MetricsConfig {
+    /// Build a [`SdkMeterProvider`] exporting to the configured destinations
+    ///
+    /// `prometheus` is registered as a reader if provided.
+    pub fn meter_provider(
+        &self,
+        prometheus: Option<PrometheusReader>,
+    ) -> crate::Result<SdkMeterProvider> {
+        let mut builder = SdkMeterProvider::builder();
+
+        if let Some(endpoint) = &self.otlp_endpoint {
+            let exporter = opentelemetry_otlp::MetricExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build()
+                .map_err(|e| crate::Error::Internal(format!("OTLP metrics exporter: {e}")))?;
+            builder = builder.with_reader(
+                PeriodicReader::builder(exporter)
+                    .with_interval(std::time::Duration::from_secs(5)) // Push interval
+                    .build(),
+            );
+        }
+
+        if let Some(reader) = prometheus {
+            builder = builder.with_reader(reader);
+        }
+
+        Ok(builder.build())
+    }
+}
+
 impl Default for Metrics {
     fn default() -> Self {
         let meter = opentelemetry::global::meter("miden-note-transport-node");
-        Self::new(&meter)
+        Self::new(&meter, &MetricsConfig::default())
     }
 }
 
@@ -278,3 +543,24 @@ impl Drop for RequestTimer<'_> {
         self.finish("dropped");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestMetrics;
+
+    #[test]
+    fn test_metrics_custom_buckets() {
+        let config = MetricsConfig {
+            size_buckets: vec![10.0, 100.0],
+            duration_buckets: vec![0.5],
+            ..Default::default()
+        };
+        let metrics = TestMetrics::with_config(&config);
+
+        metrics.metrics.grpc.grpc_send_note_request(50).finish("ok");
+
+        assert_eq!(metrics.histogram_bounds("grpc_send_note_note_size"), vec![10.0, 100.0]);
+        assert_eq!(metrics.histogram_bounds("grpc_send_note_duration"), vec![0.5]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use tonic::Status;
+use tonic::service::Interceptor;
+
+/// Bearer token authentication of requests
+///
+/// Requests must carry an `authorization: Bearer <token>` header with one of the accepted tokens.
+/// All requests are accepted if no token is configured.
+#[derive(Clone, Debug, Default)]
+pub struct AuthInterceptor {
+    tokens: Arc<BTreeSet<String>>,
+}
+
+impl AuthInterceptor {
+    /// Accept requests with any of the provided tokens
+    pub fn new(tokens: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            tokens: Arc::new(tokens.into_iter().collect()),
+        }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        if self.tokens.is_empty() {
+            return Ok(request);
+        }
+
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing bearer token"))?;
+        if !self.tokens.contains(token) {
+            return Err(Status::unauthenticated("Invalid bearer token"));
+        }
+
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(authorization: Option<&str>) -> tonic::Request<()> {
+        let mut request = tonic::Request::new(());
+        if let Some(authorization) = authorization {
+            request.metadata_mut().insert("authorization", authorization.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn test_auth_interceptor() {
+        let mut auth = AuthInterceptor::new(["secret".to_string(), "other".to_string()]);
+
+        assert!(auth.call(request(Some("Bearer secret"))).is_ok());
+        assert!(auth.call(request(Some("Bearer other"))).is_ok());
+
+        for authorization in [None, Some("Bearer wrong"), Some("secret"), Some("Basic secret")] {
+            let status = auth.call(request(authorization)).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        }
+
+        // No authentication without tokens
+        let mut auth = AuthInterceptor::default();
+        assert!(auth.call(request(None)).is_ok());
+    }
+}
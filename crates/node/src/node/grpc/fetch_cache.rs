@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::types::{NoteTag, StoredNote};
+
+/// Fetch-notes query, as cached by [`FetchCache`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct FetchKey {
+    /// Sorted, deduplicated tags
+    pub tags: Vec<u32>,
+    pub cursor: u64,
+    pub limit: Option<u32>,
+    pub exclude_acked: bool,
+    pub descending: bool,
+}
+
+/// Bounded LRU cache of fetch-notes results
+///
+/// Entries expire after `ttl`, and are invalidated when a note is stored for one of their tags.
+/// Once `capacity` entries are cached, the least recently used one is evicted. A capacity of `0`
+/// disables the cache.
+pub(super) struct FetchCache {
+    ttl: Duration,
+    capacity: usize,
+    state: Mutex<FetchCacheState>,
+}
+
+#[derive(Default)]
+struct FetchCacheState {
+    entries: BTreeMap<FetchKey, FetchEntry>,
+    /// Keys by last use
+    lru: BTreeMap<u64, FetchKey>,
+    /// Last use counter
+    tick: u64,
+    /// Invalidation counter, results queried before an invalidation are not cached
+    generation: u64,
+}
+
+struct FetchEntry {
+    notes: Vec<StoredNote>,
+    inserted_at: Instant,
+    used_at: u64,
+}
+
+impl FetchCache {
+    pub(super) fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            state: Mutex::new(FetchCacheState::default()),
+        }
+    }
+
+    /// Whether the cache is enabled
+    pub(super) fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Cached result for `key`, if any
+    pub(super) fn get(&self, key: &FetchKey) -> Option<Vec<StoredNote>> {
+        let mut state = self.state.lock().expect("Fetch cache lock poisoned");
+        state.tick += 1;
+        let tick = state.tick;
+
+        let entry = state.entries.get_mut(key)?;
+        if entry.inserted_at.elapsed() >= self.ttl {
+            let used_at = entry.used_at;
+            state.entries.remove(key);
+            state.lru.remove(&used_at);
+            return None;
+        }
+        let previous_use = std::mem::replace(&mut entry.used_at, tick);
+        let notes = entry.notes.clone();
+        state.lru.remove(&previous_use);
+        state.lru.insert(tick, key.clone());
+        Some(notes)
+    }
+
+    /// Current invalidation generation, to be passed to [`FetchCache::insert`]
+    pub(super) fn generation(&self) -> u64 {
+        self.state.lock().expect("Fetch cache lock poisoned").generation
+    }
+
+    /// Cache the result for `key`, queried at `generation`
+    ///
+    /// The result is discarded if the cache was invalidated since `generation`.
+    pub(super) fn insert(&self, key: FetchKey, notes: Vec<StoredNote>, generation: u64) {
+        if !self.enabled() {
+            return;
+        }
+        let mut state = self.state.lock().expect("Fetch cache lock poisoned");
+        if state.generation != generation {
+            return;
+        }
+        state.tick += 1;
+        let tick = state.tick;
+
+        let entry = FetchEntry {
+            notes,
+            inserted_at: Instant::now(),
+            used_at: tick,
+        };
+        if let Some(previous) = state.entries.insert(key.clone(), entry) {
+            state.lru.remove(&previous.used_at);
+        }
+        state.lru.insert(tick, key);
+
+        while state.entries.len() > self.capacity {
+            let Some((_, oldest)) = state.lru.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+    }
+
+    /// Invalidate the entries including `tag`
+    pub(super) fn invalidate_tag(&self, tag: NoteTag) {
+        let tag = tag.as_u32();
+        self.invalidate(|key| key.tags.binary_search(&tag).is_ok());
+    }
+
+    /// Invalidate the entries excluding acknowledged notes
+    pub(super) fn invalidate_acked(&self) {
+        self.invalidate(|key| key.exclude_acked);
+    }
+
+    fn invalidate(&self, filter: impl Fn(&FetchKey) -> bool) {
+        let mut state = self.state.lock().expect("Fetch cache lock poisoned");
+        state.generation += 1;
+        let FetchCacheState { entries, lru, .. } = &mut *state;
+        entries.retain(|key, entry| {
+            let keep = !filter(key);
+            if !keep {
+                lru.remove(&entry.used_at);
+            }
+            keep
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(tag: u32, cursor: u64) -> FetchKey {
+        FetchKey {
+            tags: vec![tag],
+            cursor,
+            limit: None,
+            exclude_acked: false,
+            descending: false,
+        }
+    }
+
+    #[test]
+    fn test_fetch_cache_lru() {
+        let cache = FetchCache::new(Duration::from_secs(60), 2);
+
+        cache.insert(key(1, 0), vec![], cache.generation());
+        cache.insert(key(2, 0), vec![], cache.generation());
+        // Use the first entry, the second one is evicted
+        assert!(cache.get(&key(1, 0)).is_some());
+        cache.insert(key(3, 0), vec![], cache.generation());
+        assert!(cache.get(&key(1, 0)).is_some());
+        assert!(cache.get(&key(2, 0)).is_none());
+        assert!(cache.get(&key(3, 0)).is_some());
+
+        // Results queried before an invalidation are not cached
+        let generation = cache.generation();
+        cache.invalidate_tag(NoteTag::from(3u32));
+        assert!(cache.get(&key(3, 0)).is_none());
+        cache.insert(key(3, 0), vec![], generation);
+        assert!(cache.get(&key(3, 0)).is_none());
+        assert!(cache.get(&key(1, 0)).is_some());
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use miden_note_transport_proto::miden_note_transport::SendNoteResponse;
+
+/// Bounded LRU cache of send-note responses by idempotency key
+///
+/// Entries expire after `ttl`. Once `capacity` entries are cached, the least recently used one is
+/// evicted. Keys are also recorded in the database, the cache sparing lookups of recent retries.
+pub(super) struct IdempotencyCache {
+    ttl: Duration,
+    capacity: usize,
+    state: Mutex<IdempotencyCacheState>,
+}
+
+#[derive(Default)]
+struct IdempotencyCacheState {
+    entries: BTreeMap<String, IdempotencyEntry>,
+    /// Keys by last use
+    lru: BTreeMap<u64, String>,
+    /// Last use counter
+    tick: u64,
+}
+
+struct IdempotencyEntry {
+    response: SendNoteResponse,
+    inserted_at: Instant,
+    used_at: u64,
+}
+
+impl IdempotencyCache {
+    pub(super) fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            state: Mutex::new(IdempotencyCacheState::default()),
+        }
+    }
+
+    /// Cached response for `key`, if any
+    pub(super) fn get(&self, key: &str) -> Option<SendNoteResponse> {
+        self.get_at(key, Instant::now())
+    }
+
+    /// Cache the response for `key`
+    pub(super) fn insert(&self, key: String, response: SendNoteResponse) {
+        self.insert_at(key, response, Instant::now());
+    }
+
+    fn get_at(&self, key: &str, now: Instant) -> Option<SendNoteResponse> {
+        let mut state = self.state.lock().expect("Idempotency cache lock poisoned");
+        state.tick += 1;
+        let tick = state.tick;
+
+        let entry = state.entries.get_mut(key)?;
+        if now.duration_since(entry.inserted_at) >= self.ttl {
+            let used_at = entry.used_at;
+            state.entries.remove(key);
+            state.lru.remove(&used_at);
+            return None;
+        }
+        let previous_use = std::mem::replace(&mut entry.used_at, tick);
+        let response = entry.response;
+        state.lru.remove(&previous_use);
+        state.lru.insert(tick, key.to_string());
+        Some(response)
+    }
+
+    fn insert_at(&self, key: String, response: SendNoteResponse, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().expect("Idempotency cache lock poisoned");
+        state.tick += 1;
+        let tick = state.tick;
+
+        let entry = IdempotencyEntry {
+            response,
+            inserted_at: now,
+            used_at: tick,
+        };
+        if let Some(previous) = state.entries.insert(key.clone(), entry) {
+            state.lru.remove(&previous.used_at);
+        }
+        state.lru.insert(tick, key);
+
+        while state.entries.len() > self.capacity {
+            let Some((_, oldest)) = state.lru.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miden_note_transport_proto::miden_note_transport::SendNoteStatus;
+
+    use super::*;
+
+    fn response(status: SendNoteStatus) -> SendNoteResponse {
+        SendNoteResponse { status: status.into() }
+    }
+
+    #[test]
+    fn test_idempotency_cache_ttl_and_capacity() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 2);
+        let now = Instant::now();
+
+        cache.insert_at("a".to_string(), response(SendNoteStatus::Accepted), now);
+        cache.insert_at("b".to_string(), response(SendNoteStatus::Duplicate), now);
+        assert_eq!(cache.get_at("b", now), Some(response(SendNoteStatus::Duplicate)));
+
+        // Expired
+        assert_eq!(cache.get_at("a", now + Duration::from_secs(60)), None);
+
+        // The least recently used entry is evicted once full
+        cache.insert_at("a".to_string(), response(SendNoteStatus::Accepted), now);
+        assert!(cache.get_at("b", now).is_some());
+        cache.insert_at("c".to_string(), response(SendNoteStatus::Accepted), now);
+        assert_eq!(cache.get_at("a", now), None);
+        assert!(cache.get_at("b", now).is_some());
+        assert!(cache.get_at("c", now).is_some());
+    }
+}
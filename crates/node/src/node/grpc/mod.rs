@@ -1,39 +1,116 @@
+mod auth;
+mod fetch_cache;
+mod idempotency;
+mod rate_limit;
+mod request_id;
 mod streaming;
+mod subscription_limit;
+mod tag_policy;
+mod tls;
 
 use std::collections::BTreeSet;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use std::time::Duration;
 
-use chrono::Utc;
 use miden_note_transport_proto::miden_note_transport::miden_note_transport_server::MidenNoteTransportServer;
 use miden_note_transport_proto::miden_note_transport::{
+    AckNotesRequest,
+    AckNotesResponse,
+    ExistsNotesRequest,
+    ExistsNotesResponse,
     FetchNotesRequest,
     FetchNotesResponse,
+    FetchNotesStreamRequest,
+    FetchOrder,
+    GetNoteRequest,
+    GetNoteResponse,
+    ListTagsRequest,
+    ListTagsResponse,
+    NoteHistogramBucket,
+    NoteHistogramRequest,
+    NoteHistogramResponse,
     SendNoteRequest,
     SendNoteResponse,
+    SendNoteStatus,
     StatsResponse,
     StreamNotesRequest,
+    TagStats,
     TransportNote,
 };
-use miden_objects::utils::Deserializable;
+use miden_objects::utils::{Deserializable, Serializable};
 use rand::Rng;
+use tokio::net::TcpListener;
 use tokio::sync::mpsc;
 use tonic::Status;
+use tonic::codec::CompressionEncoding;
+use tonic::codegen::http::{HeaderName, HeaderValue, Method};
+use tonic::codegen::tokio_stream::wrappers::ReceiverStream;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Server;
+use tonic::transport::server::TcpIncoming;
 use tonic_web::GrpcWebLayer;
 use tower::limit::GlobalConcurrencyLimitLayer;
 use tower::timeout::TimeoutLayer;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
 
+pub use self::auth::AuthInterceptor;
+use self::fetch_cache::{FetchCache, FetchKey};
+use self::idempotency::IdempotencyCache;
+use self::rate_limit::{RateLimited, RateLimiter};
+pub use self::request_id::{REQUEST_ID_HEADER, RequestIdLayer};
 use self::streaming::{NoteStreamer, StreamerMessage, Sub, Subface};
-use crate::database::Database;
-use crate::metrics::MetricsGrpc;
+pub use self::streaming::{SlowSubscriberPolicy, StreamerConfig};
+use self::subscription_limit::{SubscriptionLimited, SubscriptionLimiter};
+pub use self::tag_policy::TagPolicy;
+pub use self::tls::TlsConfig;
+use crate::clock::{Clock, SystemClock};
+use crate::database::{Database, DatabaseError, IdempotencyKey};
+use crate::metrics::{MetricsGrpc, MetricsStreaming};
+use crate::types::{
+    NOTE_FORMAT_VERSION,
+    NoteId,
+    NoteTag,
+    StoredNote,
+    datetime_to_proto_timestamp,
+    proto_timestamp_to_datetime,
+};
+
+/// Size of a request beyond its note, i.e. encoding of the other fields
+const MESSAGE_OVERHEAD: usize = 1024;
+
+/// Default maximum decoded message size of tonic
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Metadata of rate-limited send-note rejections, the milliseconds to wait before retrying
+pub const RETRY_AFTER_HEADER: &str = "retry-after-ms";
+
+/// Default number of notes per page of a fetch-notes stream
+const DEFAULT_FETCH_STREAM_PAGE_SIZE: u32 = 100;
+
+/// Maximum number of notes per page of a fetch-notes stream
+const MAX_FETCH_STREAM_PAGE_SIZE: u32 = 1000;
+
+/// Default number of tags of a list-tags response
+const DEFAULT_LIST_TAGS_LIMIT: u32 = 100;
+
+/// Maximum number of tags of a list-tags response
+const MAX_LIST_TAGS_LIMIT: u32 = 1000;
+
+/// Maximum length of a send-note idempotency key, recorded in the database
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 128;
 
 /// Miden Note Transport gRPC server
 pub struct GrpcServer {
     database: Arc<Database>,
     config: GrpcServerConfig,
     streamer: StreamerCtx,
+    rate_limiter: RateLimiter,
+    idempotency_cache: IdempotencyCache,
+    fetch_cache: FetchCache,
+    clock: Arc<dyn Clock>,
     metrics: MetricsGrpc,
 }
 
@@ -44,18 +121,92 @@ pub struct GrpcServerConfig {
     pub host: String,
     /// Server port
     pub port: u16,
-    /// Maximum note size to be stored
+    /// Maximum note size to be stored, header and details included
     pub max_note_size: usize,
+    /// Maximum note header size
+    pub max_header_size: usize,
+    /// Note size above which notes are counted as near the limit, `0` to disable
+    ///
+    /// Such notes are still accepted, the count warns of growth towards
+    /// [`GrpcServerConfig::max_note_size`].
+    pub warn_note_size: usize,
     /// Maximum number of concurrent connections
     pub max_connections: usize,
     /// Connection timeout in seconds
     pub request_timeout: usize,
+    /// Interval between HTTP/2 keepalive pings, disabled if unset
+    ///
+    /// Keeps idle connections, such as note streams, alive through NATs and load balancers.
+    pub http2_keepalive_interval: Option<Duration>,
+    /// Time waited for a keepalive ping acknowledgment before closing the connection
+    pub http2_keepalive_timeout: Duration,
+    /// Maximum number of tags reported in the per-tag statistics
+    ///
+    /// Only the tags with the most notes are reported, making it a truncated view.
+    pub stats_max_tags: u32,
+    /// Maximum number of notes sent per minute for a single tag, `0` for unlimited
+    pub rate_limit_per_minute: u32,
+    /// Maximum number of notes sent per minute for all tags combined, `0` for unlimited
+    pub global_rate_limit_per_minute: u32,
+    /// Note streaming configuration
+    pub streamer: StreamerConfig,
+    /// Enable note acknowledgment
+    ///
+    /// Acknowledged notes can be excluded from fetches, for mailbox-like usage. Disabled by
+    /// default, as acknowledgments are shared by all the recipients of a tag.
+    pub acks_enabled: bool,
+    /// Compression of responses, if accepted by the client
+    ///
+    /// Compressed requests are always accepted, for both `gzip` and `zstd`.
+    pub compression: Option<CompressionEncoding>,
+    /// Policy for the tags of sent notes
+    pub tag_policy: TagPolicy,
+    /// Time during which a send-note idempotency key is remembered
+    ///
+    /// Retries with the same key within it are answered with the original response.
+    pub idempotency_ttl: Duration,
+    /// Maximum number of send-note responses cached by idempotency key, `0` to disable idempotency
+    /// keys
+    ///
+    /// Keys evicted from the cache are still looked up in the database until expiry.
+    pub idempotency_capacity: usize,
+    /// Origins allowed for gRPC-web requests, any origin if unset
+    pub cors_allowed_origins: Option<Vec<String>>,
+    /// Request headers allowed for gRPC-web requests, any header if unset
+    pub cors_allowed_headers: Option<Vec<String>>,
+    /// Methods allowed for gRPC-web requests, any method if unset
+    pub cors_allowed_methods: Option<Vec<String>>,
+    /// TLS configuration, plaintext if unset
+    pub tls: Option<TlsConfig>,
+    /// Bearer tokens accepted for requests, no authentication if empty
+    pub auth_tokens: Vec<String>,
+    /// Time during which a fetch-notes result is cached
+    ///
+    /// Results are invalidated when a note is stored for one of their tags, but notes removed by
+    /// maintenance may be served until expiry.
+    pub fetch_cache_ttl: Duration,
+    /// Maximum number of cached fetch-notes results, `0` to disable the cache
+    pub fetch_cache_capacity: usize,
+    /// Maximum number of notes of a fetch-notes response, `0` for unlimited
+    ///
+    /// Larger client limits, and unlimited requests, are clamped to it.
+    pub max_fetch_limit: u32,
+    /// Maximum number of open note streams, `0` for unlimited
+    pub max_subscriptions: usize,
+    /// Maximum number of open note streams per peer IP address, `0` for unlimited
+    pub max_subscriptions_per_peer: usize,
+    /// Maximum number of note IDs of an exists-notes or ack-notes request
+    ///
+    /// IDs are bound as query parameters, the limit staying well below those of the databases.
+    pub max_note_ids: usize,
 }
 
 /// Streaming task interface context
 pub(super) struct StreamerCtx {
     tx: mpsc::Sender<StreamerMessage>,
-    handle: tokio::task::JoinHandle<()>,
+    handle: Option<tokio::task::JoinHandle<()>>,
+    /// Open subscriptions
+    subscriptions: Arc<SubscriptionLimiter>,
 }
 
 impl Default for GrpcServerConfig {
@@ -64,46 +215,263 @@ impl Default for GrpcServerConfig {
             host: "127.0.0.1".to_string(),
             port: 57292,
             max_note_size: 512_000,
+            max_header_size: 256,
+            warn_note_size: 409_600,
             max_connections: 4096,
             request_timeout: 4,
+            http2_keepalive_interval: Some(Duration::from_secs(30)),
+            http2_keepalive_timeout: Duration::from_secs(20),
+            stats_max_tags: 100,
+            rate_limit_per_minute: 600,
+            global_rate_limit_per_minute: 60_000,
+            streamer: StreamerConfig::default(),
+            acks_enabled: false,
+            compression: Some(CompressionEncoding::Gzip),
+            tag_policy: TagPolicy::default(),
+            idempotency_ttl: Duration::from_secs(600),
+            idempotency_capacity: 10_000,
+            cors_allowed_origins: None,
+            cors_allowed_headers: None,
+            cors_allowed_methods: None,
+            tls: None,
+            auth_tokens: vec![],
+            fetch_cache_ttl: Duration::from_secs(1),
+            fetch_cache_capacity: 0,
+            max_fetch_limit: 1000,
+            max_subscriptions: 10_000,
+            max_subscriptions_per_peer: 100,
+            max_note_ids: 1000,
         }
     }
 }
 
+impl GrpcServerConfig {
+    /// Maximum size of decoded requests
+    ///
+    /// Large enough for a note of [`GrpcServerConfig::max_note_size`], and at least the tonic
+    /// default.
+    pub fn max_message_size(&self) -> usize {
+        (self.max_note_size + MESSAGE_OVERHEAD).max(DEFAULT_MAX_MESSAGE_SIZE)
+    }
+
+    /// Server builder with the configured HTTP/2 keepalive
+    pub fn server_builder(&self) -> Server {
+        Server::builder()
+            .http2_keepalive_interval(self.http2_keepalive_interval)
+            .http2_keepalive_timeout(Some(self.http2_keepalive_timeout))
+    }
+
+    /// CORS layer for the configured origins, headers and methods
+    pub fn cors_layer(&self) -> crate::Result<CorsLayer> {
+        let origin = match &self.cors_allowed_origins {
+            Some(origins) => AllowOrigin::list(
+                origins
+                    .iter()
+                    .map(|origin| HeaderValue::from_str(origin))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| crate::Error::Internal(format!("Invalid CORS origin: {e}")))?,
+            ),
+            None => AllowOrigin::any(),
+        };
+        let headers = match &self.cors_allowed_headers {
+            Some(headers) => AllowHeaders::list(
+                headers
+                    .iter()
+                    .map(|header| HeaderName::from_bytes(header.as_bytes()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| crate::Error::Internal(format!("Invalid CORS header: {e}")))?,
+            ),
+            None => AllowHeaders::any(),
+        };
+        let methods = match &self.cors_allowed_methods {
+            Some(methods) => AllowMethods::list(
+                methods
+                    .iter()
+                    .map(|method| Method::from_bytes(method.as_bytes()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| crate::Error::Internal(format!("Invalid CORS method: {e}")))?,
+            ),
+            None => AllowMethods::any(),
+        };
+
+        Ok(CorsLayer::new()
+            .allow_origin(origin)
+            .allow_headers(headers)
+            .allow_methods(methods))
+    }
+}
+
 impl GrpcServer {
     /// gRPC server constructor
-    pub fn new(database: Arc<Database>, config: GrpcServerConfig, metrics: MetricsGrpc) -> Self {
-        let streamer = StreamerCtx::spawn(database.clone());
-        Self { database, config, streamer, metrics }
+    pub fn new(
+        database: Arc<Database>,
+        config: GrpcServerConfig,
+        metrics: MetricsGrpc,
+        streaming_metrics: MetricsStreaming,
+    ) -> Self {
+        let subscriptions =
+            SubscriptionLimiter::new(config.max_subscriptions, config.max_subscriptions_per_peer);
+        let streamer = StreamerCtx::spawn(
+            database.clone(),
+            config.streamer.clone(),
+            streaming_metrics,
+            subscriptions,
+        );
+        let rate_limiter =
+            RateLimiter::new(config.rate_limit_per_minute, config.global_rate_limit_per_minute);
+        let idempotency_cache =
+            IdempotencyCache::new(config.idempotency_ttl, config.idempotency_capacity);
+        let fetch_cache = FetchCache::new(config.fetch_cache_ttl, config.fetch_cache_capacity);
+        Self {
+            database,
+            config,
+            streamer,
+            rate_limiter,
+            idempotency_cache,
+            fetch_cache,
+            clock: Arc::new(SystemClock),
+            metrics,
+        }
+    }
+
+    /// Replace the [`Clock`] timestamping stored notes, [`SystemClock`] by default
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 
     /// Convert into a service
-    pub fn into_service(self) -> MidenNoteTransportServer<Self> {
-        MidenNoteTransportServer::new(self)
+    pub fn into_service(
+        self,
+    ) -> InterceptedService<MidenNoteTransportServer<Self>, AuthInterceptor> {
+        let compression = self.config.compression;
+        let max_message_size = self.config.max_message_size();
+        let auth = AuthInterceptor::new(self.config.auth_tokens.clone());
+        let service = MidenNoteTransportServer::new(self)
+            .max_decoding_message_size(max_message_size)
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
+        let service = match compression {
+            Some(encoding) => service.send_compressed(encoding),
+            None => service,
+        };
+        InterceptedService::new(service, auth)
     }
 
-    /// gRPC server running-task
-    pub async fn serve(self) -> crate::Result<()> {
-        let (health_reporter, health_svc) = tonic_health::server::health_reporter();
-        health_reporter.set_serving::<MidenNoteTransportServer<Self>>().await;
+    /// Record a send-note rejection for `reason`, returning `status`
+    fn reject_send_note(&self, reason: &str, status: Status) -> Status {
+        self.metrics.grpc_send_note_rejected(reason);
+        status
+    }
 
+    /// Idempotency key of a send-note request, expiring after
+    /// [`GrpcServerConfig::idempotency_ttl`]
+    fn idempotency_key<'a>(&self, key: &'a str) -> IdempotencyKey<'a> {
+        let expired_before = chrono::Duration::from_std(self.config.idempotency_ttl)
+            .ok()
+            .and_then(|ttl| self.clock.now().checked_sub_signed(ttl))
+            .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+        IdempotencyKey { key, expired_before }
+    }
+
+    /// Response to the request handled with an idempotency key, if any
+    async fn idempotent_response(&self, key: &str) -> Result<Option<SendNoteResponse>, Status> {
+        if let Some(response) = self.idempotency_cache.get(key) {
+            return Ok(Some(response));
+        }
+        let duplicate = self
+            .database
+            .fetch_idempotency_key(self.idempotency_key(key))
+            .await
+            .map_err(|e| Status::internal(format!("Failed to fetch idempotency key: {e:?}")))?;
+        let response = duplicate.map(|duplicate| {
+            let status = if duplicate {
+                SendNoteStatus::Duplicate
+            } else {
+                SendNoteStatus::Accepted
+            };
+            SendNoteResponse { status: status.into() }
+        });
+        if let Some(response) = response {
+            self.idempotency_cache.insert(key.to_string(), response);
+        }
+        Ok(response)
+    }
+
+    /// Reject requests with more than [`GrpcServerConfig::max_note_ids`] note IDs
+    fn check_note_ids_len(&self, len: usize) -> Result<(), Status> {
+        if len > self.config.max_note_ids {
+            return Err(Status::invalid_argument(format!(
+                "Too many note IDs ({len}), at most {} allowed",
+                self.config.max_note_ids
+            )));
+        }
+        Ok(())
+    }
+
+    /// gRPC server running-task
+    ///
+    /// Once `shutdown` completes, no new requests are accepted and in-flight ones are drained
+    /// before returning. Note streams are ended, and the [`NoteStreamer`] task is joined.
+    pub async fn serve(self, shutdown: impl Future<Output = ()>) -> crate::Result<()> {
         let addr = format!("{}:{}", self.config.host, self.config.port)
             .parse::<SocketAddr>()
             .map_err(|e| crate::Error::Internal(format!("Invalid address: {e}")))?;
+        let listener = TcpListener::bind(addr).await?;
+        tracing::info!("gRPC server listening on {}", listener.local_addr()?);
 
-        let cors = CorsLayer::new().allow_origin(Any).allow_headers(Any).allow_methods(Any);
+        self.serve_with_listener(listener, shutdown).await
+    }
 
-        tonic::transport::Server::builder()
+    /// gRPC server running-task, accepting connections from `listener`
+    ///
+    /// The configured host and port are ignored. Binding the listener to port `0` lets the OS
+    /// assign a port, read back with [`TcpListener::local_addr`]. See [`GrpcServer::serve`].
+    pub async fn serve_with_listener(
+        mut self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()>,
+    ) -> crate::Result<()> {
+        let (health_reporter, health_svc) = tonic_health::server::health_reporter();
+        health_reporter.set_serving::<MidenNoteTransportServer<Self>>().await;
+
+        let cors = self.config.cors_layer()?;
+        let mut builder = self.config.server_builder();
+        if let Some(tls) = &self.config.tls {
+            builder = builder.tls_config(tls.server_config()?)?;
+        }
+
+        let streamer_tx = self.streamer.tx.clone();
+        let streamer_handle = self.streamer.handle.take();
+        let shutdown = async move {
+            shutdown.await;
+            // Subscriber streams never end on their own, stopping the streamer closes them
+            if streamer_tx.send(StreamerMessage::Shutdown).await.is_err() {
+                tracing::error!("Streamer shutdown message sending failure");
+            }
+        };
+
+        builder
             .accept_http1(true)
+            .layer(RequestIdLayer)
             .layer(cors)
             .layer(GrpcWebLayer::new())
             .layer(GlobalConcurrencyLimitLayer::new(self.config.max_connections))
             .layer(TimeoutLayer::new(Duration::from_secs(self.config.request_timeout as u64)))
             .add_service(health_svc)
             .add_service(self.into_service())
-            .serve(addr)
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), shutdown)
             .await
-            .map_err(|e| crate::Error::Internal(format!("Server error: {e}")))
+            .map_err(|e| crate::Error::Internal(format!("Server error: {e}")))?;
+
+        if let Some(handle) = streamer_handle {
+            handle
+                .await
+                .map_err(|e| crate::Error::Internal(format!("Streamer task failure: {e}")))?;
+        }
+
+        Ok(())
     }
 }
 
@@ -111,10 +479,19 @@ impl StreamerCtx {
     /// Spawn a [`NoteStreamer`] task
     ///
     /// Returns related context composed of the handle and `mpsc::Sender` `tx` for control messages.
-    pub(super) fn spawn(database: Arc<Database>) -> Self {
+    pub(super) fn spawn(
+        database: Arc<Database>,
+        config: StreamerConfig,
+        metrics: MetricsStreaming,
+        subscriptions: SubscriptionLimiter,
+    ) -> Self {
         let (tx, rx) = mpsc::channel(128);
-        let handle = tokio::spawn(NoteStreamer::new(database, rx).stream());
-        Self { tx, handle }
+        let handle = tokio::spawn(NoteStreamer::new(database, config, metrics, rx).stream());
+        Self {
+            tx,
+            handle: Some(handle),
+            subscriptions: Arc::new(subscriptions),
+        }
     }
 }
 
@@ -128,33 +505,115 @@ impl miden_note_transport_proto::miden_note_transport::miden_note_transport_serv
         request: tonic::Request<SendNoteRequest>,
     ) -> Result<tonic::Response<SendNoteResponse>, tonic::Status> {
         let request_data = request.into_inner();
+        let idempotency_key = (!request_data.idempotency_key.is_empty() && self.config.idempotency_capacity > 0)
+            .then_some(request_data.idempotency_key);
+        if let Some(key) = &idempotency_key {
+            if key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+                return Err(Status::invalid_argument(format!("Idempotency key too long ({})", key.len())));
+            }
+            // Retry of an already handled request
+            if let Some(response) = self.idempotent_response(key).await? {
+                return Ok(tonic::Response::new(response));
+            }
+        }
         let pnote = request_data.note.ok_or_else(|| Status::invalid_argument("Missing note"))?;
 
         let timer = self.metrics.grpc_send_note_request((pnote.header.len() + pnote.details.len()) as u64);
 
         // Validate note size
-        if pnote.details.len() > self.config.max_note_size {
-            return Err(Status::resource_exhausted(format!("Note too large ({})", pnote.details.len())));
+        if pnote.header.len() > self.config.max_header_size {
+            let status = Status::resource_exhausted(format!("Note header too large ({})", pnote.header.len()));
+            return Err(self.reject_send_note("too_large", status));
+        }
+        let note_size = pnote.header.len() + pnote.details.len();
+        if note_size > self.config.max_note_size {
+            let status = Status::resource_exhausted(format!("Note too large ({note_size})"));
+            return Err(self.reject_send_note("too_large", status));
         }
+        if self.config.warn_note_size != 0 && note_size > self.config.warn_note_size {
+            self.metrics.grpc_send_note_near_limit();
+        }
+
+        // Convert protobuf request to internal types, the header size being bounded above
+        let header = read_note_header(&pnote.header)
+            .map_err(|e| self.reject_send_note("invalid_header", Status::invalid_argument(format!("Invalid header: {e}"))))?;
+
+        let tag = header.metadata().tag();
+        if !self.config.tag_policy.allows(&header) {
+            let status = Status::invalid_argument(format!("Note tag {} not allowed", tag.as_u32()));
+            return Err(self.reject_send_note("tag_not_allowed", status));
+        }
+        self.rate_limiter.check(tag).map_err(|e| {
+            let mut status = match e {
+                RateLimited::Tag(_) => Status::resource_exhausted(format!("Rate limit exceeded for tag {}", tag.as_u32())),
+                RateLimited::Global(_) => Status::resource_exhausted("Rate limit exceeded"),
+            };
+            let retry_after_ms = u64::try_from(e.retry_after().as_micros().div_ceil(1000)).unwrap_or(u64::MAX);
+            status.metadata_mut().insert(RETRY_AFTER_HEADER, retry_after_ms.into());
+            self.reject_send_note("rate_limited", status)
+        })?;
 
-        // Convert protobuf request to internal types
-        let header = miden_objects::note::NoteHeader::read_from_bytes(&pnote.header)
-            .map_err(|e| Status::invalid_argument(format!("Invalid header: {e:?}")))?;
+        let created_at = self.clock.now();
+        let expires_at = pnote
+            .expires_at
+            .map(proto_timestamp_to_datetime)
+            .transpose()
+            .map_err(|e| {
+                self.reject_send_note("invalid_expiration", Status::invalid_argument(format!("Invalid expiration time: {e}")))
+            })?;
+        if expires_at.is_some_and(|expires_at| expires_at <= created_at) {
+            let status = Status::invalid_argument("Note already expired");
+            return Err(self.reject_send_note("invalid_expiration", status));
+        }
+
+        let format_version = match pnote.format_version {
+            0 => NOTE_FORMAT_VERSION,
+            version => u8::try_from(version).map_err(|_| {
+                let status = Status::invalid_argument(format!("Invalid format version {version}"));
+                self.reject_send_note("invalid_format_version", status)
+            })?,
+        };
 
         // Create note for database
         let note_for_db = crate::types::StoredNote {
             header,
             details: pnote.details,
-            created_at: Utc::now(),
+            created_at,
+            expires_at,
+            seq: None,
+            format_version,
         };
 
-        self.database
-            .store_note(&note_for_db)
-            .await.map_err(|e| tonic::Status::internal(format!("Failed to store note: {e:?}")))?;
+        let stored = self.database
+            .store_note_with_metadata(&note_for_db, idempotency_key.as_deref().map(|key| self.idempotency_key(key)))
+            .await;
+        let inserted = match (stored, idempotency_key.as_deref()) {
+            (Ok(inserted), _) => inserted,
+            // A concurrent request with the same key was handled first, the check above being unlocked
+            (Err(DatabaseError::ConstraintViolation(e)), Some(key)) => {
+                return match self.idempotent_response(key).await? {
+                    Some(response) => Ok(tonic::Response::new(response)),
+                    None => Err(tonic::Status::internal(format!("Failed to store note: {e}"))),
+                };
+            },
+            (Err(e), _) => return Err(tonic::Status::internal(format!("Failed to store note: {e:?}"))),
+        };
+        let status = if inserted {
+            self.fetch_cache.invalidate_tag(tag);
+            SendNoteStatus::Accepted
+        } else {
+            self.metrics.grpc_send_note_duplicate();
+            SendNoteStatus::Duplicate
+        };
 
         timer.finish("ok");
 
-        Ok(tonic::Response::new(SendNoteResponse {}))
+        let response = SendNoteResponse { status: status.into() };
+        if let Some(key) = idempotency_key {
+            self.idempotency_cache.insert(key, response);
+        }
+
+        Ok(tonic::Response::new(response))
     }
 
     #[tracing::instrument(skip(self), fields(operation = "grpc.fetch_notes.request"))]
@@ -165,28 +624,78 @@ impl miden_note_transport_proto::miden_note_transport::miden_note_transport_serv
         let timer = self.metrics.grpc_fetch_notes_request();
 
         let request_data = request.into_inner();
-        let tags = request_data.tags.into_iter().collect::<BTreeSet<_>>();
+        let tags = request_data
+            .tags
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(NoteTag::from)
+            .collect::<Vec<_>>();
         let cursor = request_data.cursor;
+        let descending = match FetchOrder::try_from(request_data.order) {
+            Ok(FetchOrder::Asc) => false,
+            Ok(FetchOrder::Desc) => true,
+            Err(_) => return Err(Status::invalid_argument("Invalid fetch order")),
+        };
+        if descending && request_data.include_total {
+            return Err(Status::invalid_argument("Total is only counted in ascending order"));
+        }
+        let max_limit = self.config.max_fetch_limit;
+        let clamped = max_limit != 0 && (request_data.limit == 0 || request_data.limit > max_limit);
+        let limit = if clamped {
+            self.metrics.grpc_fetch_notes_limit_clamped();
+            Some(max_limit)
+        } else {
+            (request_data.limit != 0).then_some(request_data.limit)
+        };
 
-        let mut rcursor = cursor;
-        let mut proto_notes = vec![];
-        for tag in tags {
-            let stored_notes = self
-                .database
-                .fetch_notes(tag.into(), cursor)
-                .await.map_err(|e| tonic::Status::internal(format!("Failed to fetch notes: {e:?}")))?;
-
-            for stored_note in &stored_notes {
-                let ts_cursor: u64 = stored_note
-                    .created_at
-                    .timestamp_micros()
-                    .try_into()
-                    .map_err(|_| tonic::Status::internal("Timestamp too large for cursor"))?;
-                rcursor = rcursor.max(ts_cursor);
+        let cache_key = FetchKey {
+            tags: tags.iter().map(NoteTag::as_u32).collect(),
+            cursor,
+            limit,
+            exclude_acked: request_data.exclude_acked,
+            descending,
+        };
+        let stored_notes = if let Some(notes) = self.fetch_cache.get(&cache_key) {
+            self.metrics.grpc_fetch_notes_cache_hit();
+            notes
+        } else {
+            let generation = self.fetch_cache.generation();
+            let notes = if descending {
+                self.database
+                    .fetch_notes_desc(&tags, cursor, limit, request_data.exclude_acked)
+                    .await
+            } else if request_data.exclude_acked {
+                self.database.fetch_unacked_notes(&tags, cursor, limit).await
+            } else {
+                self.database.fetch_notes(&tags, cursor, limit).await
             }
+            .map_err(|e| tonic::Status::internal(format!("Failed to fetch notes: {e:?}")))?;
+            if self.fetch_cache.enabled() {
+                self.fetch_cache.insert(cache_key, notes.clone(), generation);
+            }
+            notes
+        };
 
-            proto_notes.extend(stored_notes.into_iter().map(TransportNote::from));
-        }
+        // In descending order, the cursor is the oldest returned note
+        let rcursor = if descending {
+            stored_notes.iter().filter_map(|note| note.seq).min().unwrap_or(cursor)
+        } else {
+            notes_cursor(cursor, &stored_notes)
+        };
+
+        let total_remaining = if request_data.include_total {
+            let count = self
+                .database
+                .count_notes_after(&tags, rcursor, request_data.exclude_acked)
+                .await
+                .map_err(|e| tonic::Status::internal(format!("Failed to count notes: {e:?}")))?;
+            Some(count)
+        } else {
+            None
+        };
+
+        let proto_notes = stored_notes.into_iter().map(TransportNote::from).collect::<Vec<_>>();
 
         timer.finish("ok");
 
@@ -196,7 +705,73 @@ impl miden_note_transport_proto::miden_note_transport::miden_note_transport_serv
             proto_notes_size,
         );
 
-        Ok(tonic::Response::new(FetchNotesResponse { notes: proto_notes, cursor: rcursor }))
+        Ok(tonic::Response::new(FetchNotesResponse {
+            notes: proto_notes,
+            cursor: rcursor,
+            total_remaining,
+        }))
+    }
+
+    type FetchNotesStreamStream = ReceiverStream<Result<FetchNotesResponse, tonic::Status>>;
+    #[tracing::instrument(skip(self), fields(operation = "grpc.fetch_notes_stream.request"))]
+    async fn fetch_notes_stream(
+        &self,
+        request: tonic::Request<FetchNotesStreamRequest>,
+    ) -> Result<tonic::Response<Self::FetchNotesStreamStream>, tonic::Status> {
+        let request_data = request.into_inner();
+        let tags = request_data
+            .tags
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(NoteTag::from)
+            .collect::<Vec<_>>();
+        let page_size = match request_data.page_size {
+            0 => DEFAULT_FETCH_STREAM_PAGE_SIZE,
+            page_size => page_size.min(MAX_FETCH_STREAM_PAGE_SIZE),
+        };
+
+        let database = self.database.clone();
+        let metrics = self.metrics.clone();
+        // Single-page buffer, pages are only fetched as the client consumes them
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            let mut cursor = request_data.cursor;
+            loop {
+                let page = database
+                    .fetch_notes(&tags, cursor, Some(page_size))
+                    .await
+                    .map(|notes| (notes_cursor(cursor, &notes), notes))
+                    .map_err(|e| tonic::Status::internal(format!("Failed to fetch notes: {e:?}")));
+                let (page_cursor, notes) = match page {
+                    Ok(page) => page,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    },
+                };
+                let exhausted = notes.len() < page_size as usize;
+                cursor = page_cursor;
+
+                if !notes.is_empty() {
+                    let notes = notes.into_iter().map(TransportNote::from).collect::<Vec<_>>();
+                    let notes_size =
+                        notes.iter().map(|note| (note.header.len() + note.details.len()) as u64).sum();
+                    metrics.grpc_fetch_notes_response(notes.len() as u64, notes_size);
+
+                    let response = FetchNotesResponse { notes, cursor, total_remaining: None };
+                    // Client gone
+                    if tx.send(Ok(response)).await.is_err() {
+                        return;
+                    }
+                }
+                if exhausted {
+                    return;
+                }
+            }
+        });
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
     }
 
     type StreamNotesStream = Sub;
@@ -205,12 +780,29 @@ impl miden_note_transport_proto::miden_note_transport::miden_note_transport_serv
         &self,
         request: tonic::Request<StreamNotesRequest>,
     ) -> Result<tonic::Response<Self::StreamNotesStream>, tonic::Status> {
+        let peer = request.remote_addr().map(|addr| addr.ip());
+        let slot = self.streamer.subscriptions.acquire(peer).map_err(|e| match e {
+            SubscriptionLimited::Peer => Status::resource_exhausted("Too many note streams for peer"),
+            SubscriptionLimited::Global => Status::resource_exhausted("Too many note streams"),
+        })?;
         let request_data = request.into_inner();
-        let tag = request_data.tag.into();
+        let tags = if request_data.tags.is_empty() {
+            vec![request_data.tag.into()]
+        } else {
+            request_data
+                .tags
+                .into_iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(NoteTag::from)
+                .collect()
+        };
+        let until = (request_data.until_cursor != 0).then_some(request_data.until_cursor);
         let id = rand::rng().random();
         let (sub_tx, sub_rx) = mpsc::channel(32);
-        let sub = Sub::new(id, tag, sub_rx, self.streamer.tx.clone());
-        let subf = Subface::new(id, tag, sub_tx);
+        let lagged = Arc::new(AtomicBool::new(false));
+        let sub = Sub::new(id, tags.clone(), sub_rx, self.streamer.tx.clone(), lagged.clone(), slot);
+        let subf = Subface::new(id, tags, sub_tx, lagged, request_data.cursor, until);
         self.streamer.tx.try_send(StreamerMessage::AddSub(subf))
                     .map_err(|e| tonic::Status::internal(format!("Failed sending internal streamer message: {e}")))?;
 
@@ -227,21 +819,1457 @@ impl miden_note_transport_proto::miden_note_transport::miden_note_transport_serv
             .get_stats()
             .await.map_err(|e| tonic::Status::internal(format!("Failed to get stats: {e:?}")))?;
 
-        let response = StatsResponse {
-            total_notes,
-            total_tags,
-            notes_per_tag: Vec::new(), // TODO: Implement notes_per_tag
-        };
+        let notes_per_tag = self
+            .database
+            .notes_per_tag(self.config.stats_max_tags)
+            .await.map_err(|e| tonic::Status::internal(format!("Failed to get notes per tag: {e:?}")))?
+            .into_iter()
+            .map(|(tag, note_count)| TagStats { tag: tag.as_u32(), note_count, last_activity: None })
+            .collect();
+
+        let response = StatsResponse { total_notes, total_tags, notes_per_tag };
 
         Ok(tonic::Response::new(response))
     }
+
+    #[tracing::instrument(skip(self), fields(operation = "grpc.ack_notes.request"))]
+    async fn ack_notes(
+        &self,
+        request: tonic::Request<AckNotesRequest>,
+    ) -> Result<tonic::Response<AckNotesResponse>, tonic::Status> {
+        if !self.config.acks_enabled {
+            return Err(Status::unimplemented("Note acknowledgment is disabled"));
+        }
+
+        let request_data = request.into_inner();
+        self.check_note_ids_len(request_data.note_ids.len())?;
+        let note_ids = request_data
+            .note_ids
+            .iter()
+            .map(|bytes| NoteId::read_from_bytes(bytes))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid note ID: {e}")))?;
+
+        let acked = self
+            .database
+            .mark_acked(&note_ids)
+            .await.map_err(|e| tonic::Status::internal(format!("Failed to acknowledge notes: {e:?}")))?;
+        if acked > 0 {
+            self.fetch_cache.invalidate_acked();
+        }
+
+        Ok(tonic::Response::new(AckNotesResponse { acked }))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "grpc.exists_notes.request"))]
+    async fn exists_notes(
+        &self,
+        request: tonic::Request<ExistsNotesRequest>,
+    ) -> Result<tonic::Response<ExistsNotesResponse>, tonic::Status> {
+        let request_data = request.into_inner();
+        self.check_note_ids_len(request_data.note_ids.len())?;
+        let note_ids = request_data
+            .note_ids
+            .iter()
+            .map(|bytes| NoteId::read_from_bytes(bytes))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid note ID: {e}")))?;
+
+        let existing = self
+            .database
+            .existing_notes(&note_ids)
+            .await.map_err(|e| tonic::Status::internal(format!("Failed to check notes: {e:?}")))?;
+
+        let note_ids = existing.iter().map(Serializable::to_bytes).collect();
+        Ok(tonic::Response::new(ExistsNotesResponse { note_ids }))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "grpc.get_note.request"))]
+    async fn get_note(
+        &self,
+        request: tonic::Request<GetNoteRequest>,
+    ) -> Result<tonic::Response<GetNoteResponse>, tonic::Status> {
+        let note_id = NoteId::read_from_bytes(&request.into_inner().note_id)
+            .map_err(|e| Status::invalid_argument(format!("Invalid note ID: {e}")))?;
+
+        let note = self
+            .database
+            .get_note(note_id)
+            .await.map_err(|e| tonic::Status::internal(format!("Failed to get note: {e:?}")))?
+            .ok_or_else(|| Status::not_found(format!("Note {note_id} not found")))?;
+
+        Ok(tonic::Response::new(GetNoteResponse { note: Some(TransportNote::from(note)) }))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "grpc.list_tags.request"))]
+    async fn list_tags(
+        &self,
+        request: tonic::Request<ListTagsRequest>,
+    ) -> Result<tonic::Response<ListTagsResponse>, tonic::Status> {
+        // Tags reveal who receives notes, only listed to authenticated clients
+        if self.config.auth_tokens.is_empty() {
+            return Err(Status::permission_denied("Listing tags requires authentication"));
+        }
+
+        let request_data = request.into_inner();
+        let limit = match request_data.limit {
+            0 => DEFAULT_LIST_TAGS_LIMIT,
+            limit => limit.min(MAX_LIST_TAGS_LIMIT),
+        };
+        let tags = self
+            .database
+            .list_tags(limit, request_data.offset)
+            .await.map_err(|e| tonic::Status::internal(format!("Failed to list tags: {e:?}")))?;
+
+        let tags = tags.into_iter().map(|tag| tag.as_u32()).collect();
+        Ok(tonic::Response::new(ListTagsResponse { tags }))
+    }
+
+    #[tracing::instrument(skip(self), fields(operation = "grpc.note_histogram.request"))]
+    async fn note_histogram(
+        &self,
+        request: tonic::Request<NoteHistogramRequest>,
+    ) -> Result<tonic::Response<NoteHistogramResponse>, tonic::Status> {
+        // Traffic patterns are only shown to authenticated clients
+        if self.config.auth_tokens.is_empty() {
+            return Err(Status::permission_denied("Note histogram requires authentication"));
+        }
+
+        let request_data = request.into_inner();
+        if request_data.bucket_secs == 0 {
+            return Err(Status::invalid_argument("Bucket duration must be positive"));
+        }
+        let from = request_data
+            .from
+            .map(proto_timestamp_to_datetime)
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid start time: {e}")))?
+            .unwrap_or(chrono::DateTime::UNIX_EPOCH);
+        let to = request_data
+            .to
+            .map(proto_timestamp_to_datetime)
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid end time: {e}")))?
+            .unwrap_or_else(|| self.clock.now());
+
+        let buckets = self
+            .database
+            .note_histogram(request_data.bucket_secs, from, to)
+            .await.map_err(|e| tonic::Status::internal(format!("Failed to count notes: {e:?}")))?;
+
+        let buckets = buckets
+            .into_iter()
+            .map(|(start, count)| NoteHistogramBucket {
+                start: Some(datetime_to_proto_timestamp(start)),
+                count,
+            })
+            .collect();
+        Ok(tonic::Response::new(NoteHistogramResponse { buckets }))
+    }
+}
+
+/// Deserialize an untrusted note header
+///
+/// Headers are made of fixed-size fields, deserialization does not allocate based on the input,
+/// the length of which is bounded by [`GrpcServerConfig::max_header_size`]. Deserialization
+/// panics on malformed input are reported as errors, which requires the `panic = "unwind"`
+/// strategy (the default), with `panic = "abort"` they abort the process.
+fn read_note_header(bytes: &[u8]) -> Result<miden_objects::note::NoteHeader, String> {
+    std::panic::catch_unwind(|| miden_objects::note::NoteHeader::read_from_bytes(bytes))
+        .map_err(|_| "malformed header".to_string())?
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// Cursor after `notes`, fetched from `cursor`
+fn notes_cursor(cursor: u64, notes: &[StoredNote]) -> u64 {
+    notes.iter().filter_map(|note| note.seq).fold(cursor, u64::max)
 }
 
 impl Drop for StreamerCtx {
     fn drop(&mut self) {
+        // Already shut down
+        if self.tx.is_closed() {
+            return;
+        }
         if let Err(e) = self.tx.try_send(StreamerMessage::Shutdown) {
             tracing::error!("Streamer shutdown message sending failure: {e}");
-            self.handle.abort();
+            if let Some(handle) = &self.handle {
+                handle.abort();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use miden_note_transport_proto::miden_note_transport::miden_note_transport_client::MidenNoteTransportClient;
+    use miden_note_transport_proto::miden_note_transport::miden_note_transport_server::MidenNoteTransport;
+    use miden_objects::utils::Serializable;
+    use serial_test::serial;
+
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::test_utils::{
+        MockClock,
+        TestMetrics,
+        spawn_test_server,
+        test_note_header,
+        test_note_header_with_tag,
+    };
+    use crate::types::NoteHeader;
+
+    async fn test_server(config: GrpcServerConfig) -> (GrpcServer, Arc<Database>, TestMetrics) {
+        let metrics = TestMetrics::new();
+        let database = Arc::new(
+            Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+                .await
+                .unwrap(),
+        );
+        let server = GrpcServer::new(
+            database.clone(),
+            config,
+            metrics.metrics.grpc.clone(),
+            metrics.metrics.streaming.clone(),
+        );
+        (server, database, metrics)
+    }
+
+    fn send_note_request(note: TransportNote) -> tonic::Request<SendNoteRequest> {
+        tonic::Request::new(SendNoteRequest { note: Some(note), ..Default::default() })
+    }
+
+    fn test_transport_note() -> TransportNote {
+        TransportNote {
+            header: test_note_header().to_bytes(),
+            details: vec![1, 2, 3, 4],
+            expires_at: None,
+            format_version: 0,
         }
     }
+
+    #[tokio::test]
+    async fn test_send_note_duplicate() {
+        let (server, database, metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let note = test_transport_note();
+        let first = server.send_note(send_note_request(note.clone())).await.unwrap();
+        let second = server.send_note(send_note_request(note)).await.unwrap();
+        assert_eq!(first.into_inner().status(), SendNoteStatus::Accepted);
+        assert_eq!(second.into_inner().status(), SendNoteStatus::Duplicate);
+
+        let (total_notes, _) = database.get_stats().await.unwrap();
+        assert_eq!(total_notes, 1);
+        assert_eq!(metrics.counter("grpc_send_note_duplicate_count"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_idempotency_key() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        // Retries with fresh notes
+        let mut responses = vec![];
+        for _ in 0..2 {
+            let request = SendNoteRequest {
+                note: Some(test_transport_note()),
+                idempotency_key: "key".to_string(),
+            };
+            let response = server.send_note(tonic::Request::new(request)).await.unwrap();
+            responses.push(response.into_inner());
+        }
+
+        assert_eq!(responses[0], responses[1]);
+        assert_eq!(responses[0].status(), SendNoteStatus::Accepted);
+        assert_eq!(database.get_stats().await.unwrap().0, 1);
+
+        // Keys are stored along with notes, surviving restarts
+        let metrics = TestMetrics::new();
+        let server = GrpcServer::new(
+            database.clone(),
+            GrpcServerConfig::default(),
+            metrics.metrics.grpc.clone(),
+            metrics.metrics.streaming.clone(),
+        );
+        let request = SendNoteRequest {
+            note: Some(test_transport_note()),
+            idempotency_key: "key".to_string(),
+        };
+        let response = server.send_note(tonic::Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner(), responses[0]);
+        assert_eq!(database.get_stats().await.unwrap().0, 1);
+
+        let request = SendNoteRequest {
+            note: Some(test_transport_note()),
+            idempotency_key: "k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1),
+        };
+        let status = server.send_note(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_idempotency_key_concurrent() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        let server = Arc::new(server);
+
+        // Concurrent retries, with the same and with fresh notes
+        let note = test_transport_note();
+        let mut requests = tokio::task::JoinSet::new();
+        for i in 0..8 {
+            let server = server.clone();
+            let note = if i % 2 == 0 {
+                note.clone()
+            } else {
+                test_transport_note()
+            };
+            requests.spawn(async move {
+                let request = SendNoteRequest {
+                    note: Some(note),
+                    idempotency_key: "key".to_string(),
+                };
+                server.send_note(tonic::Request::new(request)).await
+            });
+        }
+        while let Some(response) = requests.join_next().await {
+            let response = response.unwrap().unwrap().into_inner();
+            assert_eq!(response.status(), SendNoteStatus::Accepted);
+        }
+
+        assert_eq!(database.get_stats().await.unwrap().0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_idempotency_key_duplicate() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        let server = server.with_clock(clock.clone());
+
+        let note = test_transport_note();
+        server.send_note(send_note_request(note.clone())).await.unwrap();
+
+        // The first attempt is a duplicate, retries with fresh notes are answered the same
+        let request = SendNoteRequest {
+            note: Some(note),
+            idempotency_key: "key".to_string(),
+        };
+        let response = server.send_note(tonic::Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner().status(), SendNoteStatus::Duplicate);
+        let request = SendNoteRequest {
+            note: Some(test_transport_note()),
+            idempotency_key: "key".to_string(),
+        };
+        let response = server.send_note(tonic::Request::new(request.clone())).await.unwrap();
+        assert_eq!(response.into_inner().status(), SendNoteStatus::Duplicate);
+        assert_eq!(database.get_stats().await.unwrap().0, 1);
+
+        // Keys evicted from the cache are looked up in the database, until expiry
+        let config = GrpcServerConfig {
+            idempotency_capacity: 1,
+            ..Default::default()
+        };
+        let (server, database, _metrics) = test_server(config.clone()).await;
+        let server = server.with_clock(clock.clone());
+        let evict = |key: &str| SendNoteRequest {
+            note: Some(test_transport_note()),
+            idempotency_key: key.to_string(),
+        };
+        server.send_note(tonic::Request::new(request.clone())).await.unwrap();
+        server.send_note(tonic::Request::new(evict("other"))).await.unwrap();
+        let response = server.send_note(tonic::Request::new(request.clone())).await.unwrap();
+        assert_eq!(response.into_inner().status(), SendNoteStatus::Accepted);
+        assert_eq!(database.get_stats().await.unwrap().0, 2);
+
+        clock.advance(chrono::Duration::from_std(config.idempotency_ttl).unwrap());
+        clock.advance(chrono::Duration::seconds(1));
+        server.send_note(tonic::Request::new(evict("third"))).await.unwrap();
+        let request = SendNoteRequest {
+            note: Some(test_transport_note()),
+            ..request
+        };
+        let response = server.send_note(tonic::Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner().status(), SendNoteStatus::Accepted);
+        assert_eq!(database.get_stats().await.unwrap().0, 4);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_clock() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        let now = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let server = server.with_clock(Arc::new(MockClock::new(now)));
+
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+
+        let notes = database.fetch_notes(&[test_note_header().metadata().tag()], 0, None).await;
+        assert_eq!(notes.unwrap()[0].created_at, now);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_clock_backward() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        let server = server.with_clock(clock.clone());
+
+        let tag = 0xc000_0001u32;
+        let send = async || {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag(tag.into()).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        };
+        let fetch = async |cursor| {
+            let request = FetchNotesRequest {
+                tags: vec![tag],
+                cursor,
+                ..Default::default()
+            };
+            server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner()
+        };
+
+        send().await;
+        let response = fetch(0).await;
+        assert_eq!(response.notes.len(), 1);
+
+        // Notes stored after the clock moved backward are still fetched from the previous cursor
+        clock.advance(chrono::Duration::minutes(-10));
+        send().await;
+        let next = fetch(response.cursor).await;
+        assert_eq!(next.notes.len(), 1);
+        assert!(next.cursor > response.cursor);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_rate_limit() {
+        let config = GrpcServerConfig {
+            rate_limit_per_minute: 3,
+            ..Default::default()
+        };
+        let (server, database, _metrics) = test_server(config).await;
+
+        let tag = NoteTag::from(0xc000_0001u32);
+        for _ in 0..3 {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag(tag).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        }
+        let mut note = test_transport_note();
+        note.header = test_note_header_with_tag(tag).to_bytes();
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        // A token is refilled every 20 seconds
+        let retry_after_ms: u64 = status
+            .metadata()
+            .get(RETRY_AFTER_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(retry_after_ms > 19_000 && retry_after_ms <= 20_000, "{retry_after_ms}");
+
+        // Other tags are unaffected
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+
+        let (total_notes, _) = database.get_stats().await.unwrap();
+        assert_eq!(total_notes, 4);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_rejected_metrics() {
+        let config = GrpcServerConfig {
+            max_note_size: 1024,
+            ..Default::default()
+        };
+        let (server, database, metrics) = test_server(config).await;
+
+        let mut note = test_transport_note();
+        note.details = vec![0; 1024];
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        let mut note = test_transport_note();
+        note.header = vec![1, 2, 3];
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let rejected = |reason| {
+            metrics.counter_with_attribute("grpc_send_note_rejected_count", "reason", reason)
+        };
+        assert_eq!(rejected("too_large"), 1);
+        assert_eq!(rejected("invalid_header"), 1);
+        assert_eq!(rejected("rate_limited"), 0);
+        assert_eq!(database.get_stats().await.unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_garbage_header() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        let max_header_size = GrpcServerConfig::default().max_header_size;
+
+        let header = test_note_header().to_bytes();
+        let garbage = [
+            vec![],
+            vec![0xff],
+            header[..header.len() - 1].to_vec(),
+            [header.as_slice(), &[0][..]].concat(),
+            // Field elements out of range
+            vec![0xff; header.len()],
+            [&header[..header.len() / 2], &vec![0xff; header.len() / 2][..]].concat(),
+            vec![0xff; max_header_size],
+        ];
+        for bytes in garbage {
+            let mut note = test_transport_note();
+            note.header = bytes;
+            let send = server.send_note(send_note_request(note));
+            let status = tokio::time::timeout(Duration::from_secs(5), send)
+                .await
+                .expect("Header deserialization should not hang")
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+
+        assert_eq!(database.get_stats().await.unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_limit() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let tags = [0xc000_0001u32, 0xc000_0002u32];
+        for tag in tags.iter().cycle().take(4) {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag((*tag).into()).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        }
+
+        let request = FetchNotesRequest {
+            tags: tags.to_vec(),
+            cursor: 0,
+            limit: 3,
+            ..Default::default()
+        };
+        let response = server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner();
+        assert_eq!(response.notes.len(), 3);
+
+        // Resuming from the returned cursor yields the remaining note
+        let request = FetchNotesRequest {
+            tags: tags.to_vec(),
+            cursor: response.cursor,
+            ..Default::default()
+        };
+        let response = server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner();
+        assert_eq!(response.notes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_order() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let tags = [0xc000_0001u32, 0xc000_0002u32];
+        for tag in tags.iter().cycle().take(4) {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag((*tag).into()).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        }
+
+        let fetch = async |order: FetchOrder, cursor, limit| {
+            let request = FetchNotesRequest {
+                tags: tags.to_vec(),
+                cursor,
+                limit,
+                order: order.into(),
+                ..Default::default()
+            };
+            server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner()
+        };
+        let ascending = fetch(FetchOrder::Asc, 0, 0).await.notes;
+        let mut descending = fetch(FetchOrder::Desc, 0, 0).await.notes;
+        assert_eq!(ascending.len(), 4);
+        descending.reverse();
+        assert_eq!(descending, ascending);
+
+        // Pages continue before the returned cursor
+        let first = fetch(FetchOrder::Desc, 0, 3).await;
+        let second = fetch(FetchOrder::Desc, first.cursor, 3).await;
+        assert_eq!(first.notes, ascending[1..].iter().rev().cloned().collect::<Vec<_>>());
+        assert_eq!(second.notes, ascending[..1]);
+
+        let request = FetchNotesRequest {
+            tags: tags.to_vec(),
+            order: FetchOrder::Desc.into(),
+            include_total: true,
+            ..Default::default()
+        };
+        let status = server.fetch_notes(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_max_limit() {
+        let config = GrpcServerConfig { max_fetch_limit: 3, ..Default::default() };
+        let (server, _database, metrics) = test_server(config).await;
+
+        let tag = 0xc000_0001u32;
+        for _ in 0..5 {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag(tag.into()).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        }
+
+        let fetch = async |limit| {
+            let request = FetchNotesRequest {
+                tags: vec![tag],
+                limit,
+                ..Default::default()
+            };
+            server
+                .fetch_notes(tonic::Request::new(request))
+                .await
+                .unwrap()
+                .into_inner()
+                .notes
+                .len()
+        };
+
+        // Absurd and unlimited requests are clamped
+        assert_eq!(fetch(u32::MAX).await, 3);
+        assert_eq!(fetch(0).await, 3);
+        assert_eq!(metrics.counter("grpc_fetch_notes_limit_clamped_count"), 2);
+
+        assert_eq!(fetch(2).await, 2);
+        assert_eq!(metrics.counter("grpc_fetch_notes_limit_clamped_count"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_total_remaining() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let tag = 0xc000_0001u32;
+        for _ in 0..5 {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag(tag.into()).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        }
+
+        let mut cursor = 0;
+        for expected in [3, 1, 0] {
+            let request = FetchNotesRequest {
+                tags: vec![tag],
+                cursor,
+                limit: 2,
+                include_total: true,
+                ..Default::default()
+            };
+            let response =
+                server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner();
+            assert_eq!(response.total_remaining, Some(expected));
+            cursor = response.cursor;
+        }
+
+        // Not computed unless requested
+        let request = FetchNotesRequest { tags: vec![tag], ..Default::default() };
+        let response = server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner();
+        assert_eq!(response.total_remaining, None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_cache() {
+        let config = GrpcServerConfig {
+            fetch_cache_ttl: Duration::from_secs(60),
+            fetch_cache_capacity: 16,
+            ..Default::default()
+        };
+        let (server, _database, metrics) = test_server(config).await;
+
+        let tag = 0xc000_0001u32;
+        let send = async || {
+            let mut note = test_transport_note();
+            note.header = test_note_header_with_tag(tag.into()).to_bytes();
+            server.send_note(send_note_request(note)).await.unwrap();
+        };
+        let fetch = async || {
+            let request = FetchNotesRequest { tags: vec![tag], ..Default::default() };
+            server
+                .fetch_notes(tonic::Request::new(request))
+                .await
+                .unwrap()
+                .into_inner()
+                .notes
+                .len()
+        };
+
+        send().await;
+        assert_eq!(fetch().await, 1);
+        assert_eq!(metrics.counter("grpc_fetch_notes_cache_hit_count"), 0);
+        assert_eq!(fetch().await, 1);
+        assert_eq!(metrics.counter("grpc_fetch_notes_cache_hit_count"), 1);
+
+        // Storing a note for the tag invalidates the cached result
+        send().await;
+        assert_eq!(fetch().await, 2);
+        assert_eq!(metrics.counter("grpc_fetch_notes_cache_hit_count"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_notes_stream() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let tag = 0xc000_0001u32;
+        let start = Utc::now() - chrono::Duration::seconds(10);
+        for i in 0..1000 {
+            let note = StoredNote {
+                header: test_note_header_with_tag(tag.into()),
+                details: vec![1, 2, 3, 4],
+                created_at: start + chrono::Duration::milliseconds(i),
+                expires_at: None,
+                seq: None,
+                format_version: 1,
+            };
+            database.store_note(&note).await.unwrap();
+        }
+
+        let addr = spawn_service(server).await;
+        let mut client = MidenNoteTransportClient::connect(format!("http://{addr}")).await.unwrap();
+        let request = FetchNotesStreamRequest {
+            tags: vec![tag],
+            cursor: 0,
+            page_size: 100,
+        };
+        let mut stream = client.fetch_notes_stream(request).await.unwrap().into_inner();
+
+        let mut pages = 0;
+        let mut note_ids = BTreeSet::new();
+        let mut cursor = 0;
+        while let Some(page) = stream.message().await.unwrap() {
+            assert_eq!(page.notes.len(), 100);
+            assert!(page.cursor > cursor);
+            cursor = page.cursor;
+            for note in page.notes {
+                note_ids.insert(NoteHeader::read_from_bytes(&note.header).unwrap().id());
+            }
+            pages += 1;
+        }
+        assert_eq!(pages, 10);
+        assert_eq!(note_ids.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_ack_notes() {
+        let config = GrpcServerConfig { acks_enabled: true, ..Default::default() };
+        let (server, _database, _metrics) = test_server(config).await;
+
+        let header = test_note_header();
+        let note = TransportNote {
+            header: header.to_bytes(),
+            ..test_transport_note()
+        };
+        server.send_note(send_note_request(note)).await.unwrap();
+
+        let request = AckNotesRequest { note_ids: vec![header.id().to_bytes()] };
+        let response = server.ack_notes(tonic::Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner().acked, 1);
+
+        let tag = header.metadata().tag().as_u32();
+        let request = FetchNotesRequest {
+            tags: vec![tag],
+            exclude_acked: true,
+            ..Default::default()
+        };
+        let response = server.fetch_notes(tonic::Request::new(request)).await.unwrap();
+        assert!(response.into_inner().notes.is_empty());
+
+        // Acknowledged notes are still served to other recipients
+        let request = FetchNotesRequest { tags: vec![tag], ..Default::default() };
+        let response = server.fetch_notes(tonic::Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner().notes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ack_notes_disabled() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let request = AckNotesRequest { note_ids: vec![] };
+        let status = server.ack_notes(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unimplemented);
+    }
+
+    #[tokio::test]
+    async fn test_ack_notes_too_many_ids() {
+        let config = GrpcServerConfig {
+            acks_enabled: true,
+            max_note_ids: 2,
+            ..Default::default()
+        };
+        let (server, _database, _metrics) = test_server(config).await;
+
+        let note_ids = (0..3).map(|_| test_note_header().id().to_bytes()).collect();
+        let request = AckNotesRequest { note_ids };
+        let status = server.ack_notes(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_exists_notes() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let mut present = BTreeSet::new();
+        for _ in 0..2 {
+            let header = test_note_header();
+            let note = TransportNote {
+                header: header.to_bytes(),
+                ..test_transport_note()
+            };
+            server.send_note(send_note_request(note)).await.unwrap();
+            present.insert(header.id().to_bytes());
+        }
+        let absent = test_note_header().id().to_bytes();
+
+        let request = ExistsNotesRequest {
+            note_ids: present.iter().cloned().chain([absent]).collect(),
+        };
+        let response = server.exists_notes(tonic::Request::new(request)).await.unwrap();
+        let existing = response.into_inner().note_ids.into_iter().collect::<BTreeSet<_>>();
+        assert_eq!(existing, present);
+
+        let request = ExistsNotesRequest { note_ids: vec![vec![0xff; 3]] };
+        let status = server.exists_notes(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_exists_notes_too_many_ids() {
+        let config = GrpcServerConfig { max_note_ids: 2, ..Default::default() };
+        let (server, _database, _metrics) = test_server(config).await;
+
+        let note_ids = (0..2).map(|_| test_note_header().id().to_bytes()).collect::<Vec<_>>();
+        let request = ExistsNotesRequest { note_ids: note_ids.clone() };
+        let response = server.exists_notes(tonic::Request::new(request)).await.unwrap();
+        assert!(response.into_inner().note_ids.is_empty());
+
+        let request = ExistsNotesRequest {
+            note_ids: note_ids.into_iter().chain([test_note_header().id().to_bytes()]).collect(),
+        };
+        let status = server.exists_notes(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_note() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let header = test_note_header();
+        let note = TransportNote {
+            header: header.to_bytes(),
+            ..test_transport_note()
+        };
+        server.send_note(send_note_request(note.clone())).await.unwrap();
+
+        let request = GetNoteRequest { note_id: header.id().to_bytes() };
+        let response = server.get_note(tonic::Request::new(request)).await.unwrap();
+        let fetched = response.into_inner().note.unwrap();
+        assert_eq!(fetched.header, note.header);
+        assert_eq!(fetched.details, note.details);
+
+        let request = GetNoteRequest {
+            note_id: test_note_header().id().to_bytes(),
+        };
+        let status = server.get_note(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let request = GetNoteRequest { note_id: vec![0xff; 3] };
+        let status = server.get_note(tonic::Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origins() {
+        use tonic::codegen::http::{Request, Response, header};
+        use tower::{Layer, ServiceExt, service_fn};
+
+        /// Allowed origin of a request from `origin`, if any
+        async fn allowed_origin(config: &GrpcServerConfig, origin: &str) -> Option<HeaderValue> {
+            let service = config.cors_layer().unwrap().layer(service_fn(|_| async {
+                Ok::<_, std::convert::Infallible>(Response::new(()))
+            }));
+            let request = Request::builder().header(header::ORIGIN, origin).body(()).unwrap();
+            let response = service.oneshot(request).await.unwrap();
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).cloned()
+        }
+
+        // Any origin by default
+        let config = GrpcServerConfig::default();
+        assert!(allowed_origin(&config, "https://example.com").await.is_some());
+
+        let config = GrpcServerConfig {
+            cors_allowed_origins: Some(vec!["https://wallet.example.com".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            allowed_origin(&config, "https://wallet.example.com").await.unwrap(),
+            "https://wallet.example.com"
+        );
+        assert!(allowed_origin(&config, "https://example.com").await.is_none());
+
+        let config = GrpcServerConfig {
+            cors_allowed_methods: Some(vec!["NOT A METHOD".to_string()]),
+            ..Default::default()
+        };
+        assert!(config.cors_layer().is_err());
+    }
+
+    /// Serve the [`GrpcServer`] service on a local port
+    async fn spawn_service(server: GrpcServer) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(server.into_service())
+                .serve_with_incoming(TcpIncoming::from(listener)),
+        );
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_stream_notes_keepalive() {
+        let config = GrpcServerConfig {
+            http2_keepalive_interval: Some(Duration::from_millis(50)),
+            http2_keepalive_timeout: Duration::from_secs(1),
+            streamer: StreamerConfig {
+                poll_interval: Duration::from_millis(50),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut builder = config.server_builder();
+        let (server, database, _metrics) = test_server(config).await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            builder
+                .add_service(server.into_service())
+                .serve_with_incoming(TcpIncoming::from(listener)),
+        );
+        let mut client = MidenNoteTransportClient::connect(format!("http://{addr}")).await.unwrap();
+
+        let tag = 0xc000_0001u32;
+        let request = StreamNotesRequest { tag, ..Default::default() };
+        let mut stream = client.stream_notes(request).await.unwrap().into_inner();
+
+        // Idle for several keepalive intervals
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let note = StoredNote {
+            header: test_note_header_with_tag(tag.into()),
+            details: vec![1, 2, 3, 4],
+            created_at: Utc::now(),
+            expires_at: None,
+            seq: None,
+            format_version: 1,
+        };
+        database.store_note(&note).await.unwrap();
+
+        let update = tokio::time::timeout(Duration::from_secs(5), stream.message())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.notes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_notes_until_cursor_live_tag() {
+        let config = GrpcServerConfig {
+            port: 0,
+            streamer: StreamerConfig {
+                poll_interval: Duration::from_millis(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let server = spawn_test_server(config).await.unwrap();
+        let mut client = server.client().await;
+
+        // The tag is already tracked by the streamer
+        let tag = 0xc000_0001u32;
+        let request = StreamNotesRequest { tag, ..Default::default() };
+        let mut live_stream = client.stream_notes(request).await.unwrap().into_inner();
+
+        let mut note_ids = vec![];
+        for _ in 0..2 {
+            let header = test_note_header_with_tag(tag.into());
+            let note = TransportNote {
+                header: header.to_bytes(),
+                ..test_transport_note()
+            };
+            client.send_note(send_note_request(note)).await.unwrap();
+            note_ids.push(header.id());
+        }
+        let mut received = 0;
+        while received < note_ids.len() {
+            let update = tokio::time::timeout(Duration::from_secs(5), live_stream.message())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            received += update.notes.len();
+        }
+
+        // Bounded to the first note, stored before the stream
+        let stored = server.database.fetch_notes(&[tag.into()], 0, None).await.unwrap();
+        let until = stored[0].seq.unwrap();
+        let request = StreamNotesRequest {
+            tag,
+            cursor: 0,
+            until_cursor: until,
+            ..Default::default()
+        };
+        let mut stream = client.stream_notes(request).await.unwrap().into_inner();
+
+        let mut notes = vec![];
+        while let Some(update) = tokio::time::timeout(Duration::from_secs(5), stream.message())
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            assert!(update.cursor <= until);
+            notes.extend(update.notes);
+        }
+        assert_eq!(notes.len(), 1);
+        let header = NoteHeader::read_from_bytes(&notes[0].header).unwrap();
+        assert_eq!(header.id(), note_ids[0]);
+    }
+
+    #[tokio::test]
+    #[serial(open_telemetry_tracing)]
+    async fn test_request_id() {
+        let (mut rx_export, _rx_shutdown) = crate::logging::setup_test_tracing().unwrap();
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .layer(RequestIdLayer)
+                .add_service(server.into_service())
+                .serve_with_incoming(TcpIncoming::from(listener)),
+        );
+        let mut client = MidenNoteTransportClient::connect(format!("http://{addr}")).await.unwrap();
+
+        let mut request = tonic::Request::new(());
+        request
+            .metadata_mut()
+            .insert(REQUEST_ID_HEADER, "test-request-id".parse().unwrap());
+        let response = client.stats(request).await.unwrap();
+        assert_eq!(response.metadata().get(REQUEST_ID_HEADER).unwrap(), "test-request-id");
+
+        // Generated if not provided
+        let response = client.stats(()).await.unwrap();
+        assert!(response.metadata().get(REQUEST_ID_HEADER).is_some());
+
+        // Spans are exported in batches
+        let span = tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                let span = rx_export.recv().await.unwrap();
+                let has_id = span.attributes.iter().any(|kv| {
+                    kv.key.as_str() == "request_id" && kv.value.as_str() == "test-request-id"
+                });
+                if has_id {
+                    break span;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(span.name, "grpc.request");
+    }
+
+    #[tokio::test]
+    async fn test_auth_tokens() {
+        let config = GrpcServerConfig {
+            auth_tokens: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let (server, _database, _metrics) = test_server(config).await;
+        let addr = spawn_service(server).await;
+        let mut client = MidenNoteTransportClient::connect(format!("http://{addr}")).await.unwrap();
+
+        let status = client.stats(()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = tonic::Request::new(());
+        request.metadata_mut().insert("authorization", "Bearer secret".parse().unwrap());
+        client.stats(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_tags_requires_auth() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+
+        let request = tonic::Request::new(ListTagsRequest::default());
+        let status = server.list_tags(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let config = GrpcServerConfig {
+            auth_tokens: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let (server, _database, _metrics) = test_server(config).await;
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+
+        let request = tonic::Request::new(ListTagsRequest::default());
+        let tags = server.list_tags(request).await.unwrap().into_inner().tags;
+        assert_eq!(tags, vec![test_note_header().metadata().tag().as_u32()]);
+    }
+
+    #[tokio::test]
+    async fn test_note_histogram_requires_auth() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let request =
+            tonic::Request::new(NoteHistogramRequest { bucket_secs: 60, ..Default::default() });
+        let status = server.note_histogram(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let config = GrpcServerConfig {
+            auth_tokens: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let (server, _database, _metrics) = test_server(config).await;
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+
+        let request = tonic::Request::new(NoteHistogramRequest::default());
+        let status = server.note_histogram(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let request = tonic::Request::new(NoteHistogramRequest {
+            bucket_secs: 3600,
+            from: None,
+            to: Some(datetime_to_proto_timestamp(Utc::now() + chrono::Duration::minutes(1))),
+        });
+        let buckets = server.note_histogram(request).await.unwrap().into_inner().buckets;
+        assert_eq!(buckets.iter().map(|bucket| bucket.count).sum::<u64>(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mtls() {
+        use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
+
+        let testdata = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/tls");
+        let tls = TlsConfig {
+            server_cert: testdata.join("server.pem"),
+            server_key: testdata.join("server.key"),
+            client_ca: Some(testdata.join("ca.pem")),
+        };
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .tls_config(tls.server_config().unwrap())
+                .unwrap()
+                .add_service(server.into_service())
+                .serve_with_incoming(TcpIncoming::from(listener)),
+        );
+
+        let ca = Certificate::from_pem(std::fs::read(testdata.join("ca.pem")).unwrap());
+        let stats = async |client_tls: ClientTlsConfig| {
+            let channel = Endpoint::from_shared(format!("https://{addr}"))
+                .unwrap()
+                .tls_config(client_tls)
+                .unwrap()
+                .connect()
+                .await
+                .map_err(|e| tonic::Status::unavailable(e.to_string()))?;
+            MidenNoteTransportClient::new(channel).stats(()).await
+        };
+
+        // Without a client certificate
+        let client_tls = ClientTlsConfig::new().ca_certificate(ca.clone()).domain_name("localhost");
+        assert!(stats(client_tls).await.is_err());
+
+        // With a client certificate issued by the CA
+        let identity = Identity::from_pem(
+            std::fs::read(testdata.join("client.pem")).unwrap(),
+            std::fs::read(testdata.join("client.key")).unwrap(),
+        );
+        let client_tls = ClientTlsConfig::new()
+            .ca_certificate(ca)
+            .domain_name("localhost")
+            .identity(identity);
+        stats(client_tls).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compression_interop() {
+        for (server_compression, client_compression) in [
+            (Some(CompressionEncoding::Gzip), Some(CompressionEncoding::Gzip)),
+            (Some(CompressionEncoding::Zstd), None),
+            (None, Some(CompressionEncoding::Gzip)),
+        ] {
+            let config = GrpcServerConfig {
+                compression: server_compression,
+                ..Default::default()
+            };
+            let (server, _database, _metrics) = test_server(config).await;
+            let addr = spawn_service(server).await;
+
+            let mut client =
+                MidenNoteTransportClient::connect(format!("http://{addr}")).await.unwrap();
+            if let Some(encoding) = client_compression {
+                client = client.send_compressed(encoding).accept_compressed(encoding);
+            }
+
+            let header = test_note_header();
+            let note = TransportNote {
+                header: header.to_bytes(),
+                details: vec![7; 200_000],
+                expires_at: None,
+                format_version: 0,
+            };
+            client.send_note(send_note_request(note.clone())).await.unwrap();
+
+            let request = FetchNotesRequest {
+                tags: vec![header.metadata().tag().as_u32()],
+                ..Default::default()
+            };
+            let response = client.fetch_notes(request).await.unwrap().into_inner();
+            assert_eq!(response.notes, vec![note]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_note_raised_size_limit() {
+        let max_note_size = 6 * 1024 * 1024;
+        let config = GrpcServerConfig { max_note_size, ..Default::default() };
+        let (server, _database, _metrics) = test_server(config).await;
+        let addr = spawn_service(server).await;
+
+        // Clients fetching large notes must raise their limit too
+        let mut client = MidenNoteTransportClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap()
+            .max_decoding_message_size(max_note_size + MESSAGE_OVERHEAD);
+
+        let header = test_note_header();
+        let header_bytes = header.to_bytes();
+        let note = TransportNote {
+            details: vec![7; max_note_size - header_bytes.len()],
+            header: header_bytes,
+            expires_at: None,
+            format_version: 0,
+        };
+        client.send_note(send_note_request(note.clone())).await.unwrap();
+
+        let request = FetchNotesRequest {
+            tags: vec![header.metadata().tag().as_u32()],
+            ..Default::default()
+        };
+        let response = client.fetch_notes(request).await.unwrap().into_inner();
+        assert_eq!(response.notes, vec![note]);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_tag_policy() {
+        let config = GrpcServerConfig {
+            tag_policy: TagPolicy::RequirePrivate,
+            ..Default::default()
+        };
+        let (server, _database, _metrics) = test_server(config).await;
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+
+        let rejected_tag = NoteTag::from(0xc000_0001u32);
+        let config = GrpcServerConfig {
+            tag_policy: TagPolicy::Custom(Arc::new(move |tag| tag != rejected_tag)),
+            ..Default::default()
+        };
+        let (server, database, _metrics) = test_server(config).await;
+        let mut note = test_transport_note();
+        note.header = test_note_header_with_tag(rejected_tag).to_bytes();
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(database.get_stats().await.unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_test_server() {
+        let config = GrpcServerConfig { port: 0, ..Default::default() };
+        let server = spawn_test_server(config).await.unwrap();
+        assert_ne!(server.addr.port(), 0);
+        let mut client = server.client().await;
+
+        let header = test_note_header();
+        let note = TransportNote {
+            header: header.to_bytes(),
+            ..test_transport_note()
+        };
+        client.send_note(send_note_request(note.clone())).await.unwrap();
+        assert!(server.database.note_exists(header.id()).await.unwrap());
+
+        let request = FetchNotesRequest {
+            tags: vec![header.metadata().tag().as_u32()],
+            ..Default::default()
+        };
+        let response = client.fetch_notes(request).await.unwrap().into_inner();
+        assert_eq!(response.notes, vec![note]);
+    }
+
+    #[tokio::test]
+    async fn test_serve_shutdown() {
+        let config = GrpcServerConfig { port: 0, ..Default::default() };
+        let (server, _database, _metrics) = test_server(config).await;
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let serve = tokio::spawn(server.serve(async {
+            let _ = shutdown_rx.await;
+        }));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        shutdown_tx.send(()).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), serve)
+            .await
+            .expect("server should shut down")
+            .unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_serve_with_listener() {
+        let (server, _database, _metrics) = test_server(GrpcServerConfig::default()).await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert_ne!(addr.port(), 0);
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let serve = tokio::spawn(server.serve_with_listener(listener, async {
+            let _ = shutdown_rx.await;
+        }));
+
+        let mut client = MidenNoteTransportClient::connect(format!("http://{addr}")).await.unwrap();
+        client.stats(()).await.unwrap();
+
+        shutdown_tx.send(()).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), serve)
+            .await
+            .expect("server should shut down")
+            .unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_note_oversized_header() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let mut note = test_transport_note();
+        note.header.resize(GrpcServerConfig::default().max_header_size + 1, 0);
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(database.get_stats().await.unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_oversized_note() {
+        let config = GrpcServerConfig {
+            max_note_size: 1024,
+            ..Default::default()
+        };
+        let (server, database, _metrics) = test_server(config).await;
+
+        // Details alone fit, but not together with the header
+        let mut note = test_transport_note();
+        note.details = vec![0; 1024 - note.header.len() + 1];
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(database.get_stats().await.unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_near_limit() {
+        let config = GrpcServerConfig {
+            max_note_size: 1024,
+            warn_note_size: 800,
+            ..Default::default()
+        };
+        let (server, database, metrics) = test_server(config).await;
+
+        // Stored, but counted as near the limit
+        let mut note = test_transport_note();
+        note.details = vec![0; 900 - note.header.len()];
+        server.send_note(send_note_request(note)).await.unwrap();
+        assert_eq!(metrics.counter("grpc_send_note_near_limit_count"), 1);
+        assert_eq!(database.get_stats().await.unwrap().0, 1);
+
+        server.send_note(send_note_request(test_transport_note())).await.unwrap();
+        assert_eq!(metrics.counter("grpc_send_note_near_limit_count"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_note_malformed_header() {
+        let (server, database, _metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let mut note = test_transport_note();
+        note.header.truncate(note.header.len() / 2);
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(database.get_stats().await.unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_notes_max_subscriptions() {
+        let config = GrpcServerConfig {
+            max_subscriptions: 2,
+            ..Default::default()
+        };
+        let (server, _database, _metrics) = test_server(config).await;
+        let request =
+            || tonic::Request::new(StreamNotesRequest { tag: 0xc000_0001, ..Default::default() });
+
+        let first = server.stream_notes(request()).await.unwrap();
+        let _second = server.stream_notes(request()).await.unwrap();
+        let status = server.stream_notes(request()).await.err().unwrap();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(server.streamer.subscriptions.open(), 2);
+
+        // Closed streams are released
+        drop(first);
+        assert!(server.stream_notes(request()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_note_format_version() {
+        let (server, _database, metrics) = test_server(GrpcServerConfig::default()).await;
+
+        let tag = 0xc000_0001u32;
+        for format_version in [0, 2] {
+            let note = TransportNote {
+                header: test_note_header_with_tag(tag.into()).to_bytes(),
+                format_version,
+                ..test_transport_note()
+            };
+            server.send_note(send_note_request(note)).await.unwrap();
+        }
+        let note = TransportNote {
+            format_version: 256,
+            ..test_transport_note()
+        };
+        let status = server.send_note(send_note_request(note)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(
+            metrics.counter_with_attribute(
+                "grpc_send_note_rejected_count",
+                "reason",
+                "invalid_format_version"
+            ),
+            1
+        );
+
+        // Unset versions are stored as the current one
+        let request = FetchNotesRequest { tags: vec![tag], ..Default::default() };
+        let response = server.fetch_notes(tonic::Request::new(request)).await.unwrap().into_inner();
+        let versions: Vec<_> = response.notes.iter().map(|note| note.format_version).collect();
+        assert_eq!(versions, [u32::from(NOTE_FORMAT_VERSION), 2]);
+    }
 }
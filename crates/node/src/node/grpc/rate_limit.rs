@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::types::NoteTag;
+
+/// Interval between removals of inactive buckets
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Token-bucket rate limiter for sent notes, per tag and global
+///
+/// A limit of `0` disables the respective check.
+pub(super) struct RateLimiter {
+    per_tag: u32,
+    global: u32,
+    state: Mutex<RateLimiterState>,
+}
+
+/// Reason for a rejection by the [`RateLimiter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RateLimited {
+    /// Tag limit exceeded, with the wait before a note is accepted
+    Tag(Duration),
+    /// Global limit exceeded, with the wait before a note is accepted
+    Global(Duration),
+}
+
+struct RateLimiterState {
+    tags: BTreeMap<NoteTag, Bucket>,
+    global: Bucket,
+    last_prune: Instant,
+}
+
+/// Token bucket, refilled continuously up to a capacity of `limit` tokens per minute
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    pub(super) fn new(per_tag: u32, global: u32) -> Self {
+        let now = Instant::now();
+        let state = RateLimiterState {
+            tags: BTreeMap::new(),
+            global: Bucket::new(global, now),
+            last_prune: now,
+        };
+        Self {
+            per_tag,
+            global,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Consume a token for a note with `tag`
+    pub(super) fn check(&self, tag: NoteTag) -> Result<(), RateLimited> {
+        self.check_at(tag, Instant::now())
+    }
+
+    fn check_at(&self, tag: NoteTag, now: Instant) -> Result<(), RateLimited> {
+        let mut state = self.state.lock().expect("Rate limiter lock poisoned");
+
+        if now.duration_since(state.last_prune) >= PRUNE_INTERVAL {
+            // A full bucket is equivalent to a new one
+            let per_tag = self.per_tag;
+            state.tags.retain(|_, bucket| {
+                bucket.refill(per_tag, now);
+                !bucket.is_full(per_tag)
+            });
+            state.last_prune = now;
+        }
+
+        let global_ok = self.global == 0 || state.global.refill(self.global, now) >= 1.0;
+        if !global_ok {
+            return Err(RateLimited::Global(state.global.retry_after(self.global)));
+        }
+
+        if self.per_tag != 0 {
+            let bucket = state.tags.entry(tag).or_insert_with(|| Bucket::new(self.per_tag, now));
+            if bucket.refill(self.per_tag, now) < 1.0 {
+                return Err(RateLimited::Tag(bucket.retry_after(self.per_tag)));
+            }
+            bucket.tokens -= 1.0;
+        }
+        if self.global != 0 {
+            state.global.tokens -= 1.0;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn tracked_tags(&self) -> usize {
+        self.state.lock().expect("Rate limiter lock poisoned").tags.len()
+    }
+}
+
+impl RateLimited {
+    /// Wait before a note is accepted
+    pub(super) fn retry_after(self) -> Duration {
+        match self {
+            Self::Tag(retry_after) | Self::Global(retry_after) => retry_after,
+        }
+    }
+}
+
+impl Bucket {
+    fn new(limit: u32, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit),
+            updated_at: now,
+        }
+    }
+
+    /// Refill the bucket, returning the available tokens
+    fn refill(&mut self, limit: u32, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        let capacity = f64::from(limit);
+        self.tokens = (self.tokens + elapsed * capacity / 60.0).min(capacity);
+        self.updated_at = now;
+        self.tokens
+    }
+
+    /// Wait until a token is available
+    fn retry_after(&self, limit: u32) -> Duration {
+        Duration::from_secs_f64((1.0 - self.tokens).max(0.0) * 60.0 / f64::from(limit))
+    }
+
+    fn is_full(&self, limit: u32) -> bool {
+        self.tokens >= f64::from(limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_global() {
+        let limiter = RateLimiter::new(0, 2);
+        let now = Instant::now();
+
+        assert_eq!(limiter.check_at(NoteTag::from(1u32), now), Ok(()));
+        assert_eq!(limiter.check_at(NoteTag::from(2u32), now), Ok(()));
+        assert_eq!(
+            limiter.check_at(NoteTag::from(3u32), now),
+            Err(RateLimited::Global(Duration::from_secs(30)))
+        );
+
+        // One token is refilled every 30 seconds
+        let later = now + Duration::from_secs(30);
+        assert_eq!(limiter.check_at(NoteTag::from(3u32), later), Ok(()));
+    }
+
+    #[test]
+    fn test_rate_limiter_prune() {
+        let limiter = RateLimiter::new(2, 0);
+        let now = Instant::now();
+
+        limiter.check_at(NoteTag::from(1u32), now).unwrap();
+        limiter.check_at(NoteTag::from(2u32), now).unwrap();
+        assert_eq!(limiter.tracked_tags(), 2);
+
+        // Buckets are refilled after a minute of inactivity
+        let later = now + PRUNE_INTERVAL;
+        limiter.check_at(NoteTag::from(3u32), later).unwrap();
+        assert_eq!(limiter.tracked_tags(), 1);
+    }
+}
@@ -0,0 +1,76 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use rand::Rng;
+use tonic::codegen::http::{HeaderValue, Request, Response};
+use tower::{Layer, Service};
+use tracing::Instrument;
+
+/// Request correlation ID header
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Request correlation ID tagging
+///
+/// The ID is taken from the `x-request-id` header, or generated if absent. Requests are handled
+/// in a `grpc.request` span carrying it as `request_id`, and it is echoed in response headers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+/// Service of [`RequestIdLayer`]
+#[derive(Clone, Debug)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .filter(|value| value.to_str().is_ok())
+            .cloned()
+            .unwrap_or_else(generate_request_id);
+        request.headers_mut().insert(REQUEST_ID_HEADER, request_id.clone());
+
+        let span = tracing::info_span!(
+            "grpc.request",
+            request_id = request_id.to_str().unwrap_or_default()
+        );
+        let future = span.in_scope(|| self.inner.call(request));
+        Box::pin(
+            async move {
+                let mut response = future.await?;
+                response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Random 128-bit request ID, hex-encoded
+fn generate_request_id() -> HeaderValue {
+    let id = rand::rng().random::<u128>();
+    HeaderValue::from_str(&format!("{id:032x}")).expect("Hex should be a valid header value")
+}
@@ -1,16 +1,40 @@
 use core::task::{Poll, Waker};
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use miden_note_transport_proto::miden_note_transport::{StreamNotesUpdate, TransportNote};
 use tokio::sync::mpsc;
-use tokio::time::{Duration, sleep};
+use tokio::sync::mpsc::error::{SendTimeoutError, TrySendError};
+use tokio::time::{Duration, Instant, sleep};
 
+use super::subscription_limit::SubscriptionSlot;
 use crate::database::Database;
+use crate::metrics::MetricsStreaming;
 use crate::types::NoteTag;
 
-/// Notes (proto) with pagination
-pub type TransportNotesPg = (Vec<TransportNote>, u64);
+/// Notes (proto) with their subscribed tag, and pagination
+pub type TransportNotesPg = (Vec<(TransportNote, NoteTag)>, u64);
+
+/// Maximum number of notes of an update of a bounded sub catching up, if
+/// [`StreamerConfig::max_batch`] is unset
+const BACKFILL_PAGE_SIZE: u32 = 1000;
+
+/// Maximum size of the notes of an update of a bounded sub catching up, a single note excepted
+///
+/// Well below the default 4 MiB message size limit of clients.
+const BACKFILL_UPDATE_SIZE: usize = 2 * 1024 * 1024;
+
+/// New notes of a tag, from a database query
+pub(super) struct TagUpdate {
+    tag: NoteTag,
+    /// Notes with their cursors, sorted by cursor
+    notes: Vec<(TransportNote, u64)>,
+    /// Cursor of the most recent note
+    cursor: u64,
+    /// All notes stored up to this cursor were queried
+    synced_at: u64,
+}
 
 /// Streaming handler
 pub struct NoteStreamer {
@@ -25,111 +49,357 @@ pub struct NoteStreamer {
 struct NoteStreamerManager {
     /// Tracked tags
     tags: BTreeMap<NoteTag, TagData>,
+    /// Bounded subs catching up with the notes stored before they were added, by sub ID
+    ///
+    /// Subs join their tags once caught up.
+    backfills: BTreeMap<u64, Subface>,
     /// Sub wakers
     wakers: BTreeMap<u64, Waker>,
+    /// Last update sent to each sub
+    activity: BTreeMap<u64, SubActivity>,
     /// Database
     database: Arc<Database>,
+    /// Configuration
+    config: StreamerConfig,
+    /// Metrics
+    metrics: MetricsStreaming,
+}
+
+/// [`NoteStreamer`] configuration
+#[derive(Clone, Debug)]
+pub struct StreamerConfig {
+    /// Interval between database queries for new notes
+    pub poll_interval: Duration,
+    /// Maximum number of notes queried per tag and poll, unlimited if unset
+    ///
+    /// Remaining notes are delivered in the following polls. Also bounds the updates of bounded
+    /// subs catching up, to 1000 notes if unset.
+    pub max_batch: Option<u32>,
+    /// Handling of subscribers not keeping up with updates
+    pub slow_policy: SlowSubscriberPolicy,
+    /// Interval without updates after which an empty update is sent, disabled if unset
+    ///
+    /// Allows subscribers and intermediaries to tell idle streams from dead ones.
+    pub heartbeat_interval: Option<Duration>,
+}
+
+/// Handling of streaming subscribers not keeping up with updates
+///
+/// Each subscriber buffers a limited number of updates. This policy is applied when the buffer is
+/// full. Dropped subscribers are notified with a terminal `RESOURCE_EXHAUSTED` error.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlowSubscriberPolicy {
+    /// Drop the subscriber immediately
+    #[default]
+    Drop,
+    /// Wait up to the provided duration for buffer space, then drop the subscriber
+    ///
+    /// Other subscribers are delayed while waiting.
+    Lag(Duration),
 }
 
 /// Internal control message exchanged with the [`NoteStreamer`]
 pub(crate) enum StreamerMessage {
     /// New sub
     AddSub(Subface),
-    /// Remove sub
-    RemoveSub((u64, NoteTag)),
+    /// Remove sub, from all its tags
+    RemoveSub((u64, Vec<NoteTag>)),
     /// Update waker for sub
     Waker((u64, Waker)),
     /// Shutdown the streamer
     Shutdown,
 }
 
+/// Last update sent to a sub
+struct SubActivity {
+    at: Instant,
+    cursor: u64,
+}
+
 /// Tag data tracking
 pub struct TagData {
     lts: u64,
-    subs: BTreeMap<u64, mpsc::Sender<TransportNotesPg>>,
+    /// All notes stored up to this cursor were queried
+    synced_at: u64,
+    subs: BTreeMap<u64, Subface>,
 }
 
 /// Subscription
 pub struct Sub {
     id: u64,
-    tag: NoteTag,
+    tags: Vec<NoteTag>,
     rx: mpsc::Receiver<TransportNotesPg>,
     streamer_tx: mpsc::Sender<StreamerMessage>,
+    /// Set when the sub is dropped for being too slow
+    lagged: Arc<AtomicBool>,
+    /// Released when the sub is dropped
+    _slot: SubscriptionSlot,
 }
 
 /// Subscription interface
+#[derive(Clone)]
 pub struct Subface {
     id: u64,
-    tag: NoteTag,
+    tags: Vec<NoteTag>,
     tx: mpsc::Sender<TransportNotesPg>,
+    lagged: Arc<AtomicBool>,
+    /// Lower cursor bound, only notes stored after it are forwarded
+    cursor: u64,
+    /// Upper cursor bound, the sub completes once all notes up to it are forwarded
+    until: Option<u64>,
 }
 
 impl NoteStreamerManager {
-    pub fn new(database: Arc<Database>) -> Self {
+    pub fn new(database: Arc<Database>, config: StreamerConfig, metrics: MetricsStreaming) -> Self {
         Self {
             tags: BTreeMap::new(),
+            backfills: BTreeMap::new(),
             wakers: BTreeMap::new(),
+            activity: BTreeMap::new(),
             database,
+            config,
+            metrics,
         }
     }
 
-    pub(super) async fn query_updates(&self) -> crate::Result<Vec<(NoteTag, TransportNotesPg)>> {
+    pub(super) async fn query_updates(&self) -> crate::Result<Vec<TagUpdate>> {
         // Update period
-        sleep(Duration::from_millis(500)).await;
+        sleep(self.config.poll_interval).await;
 
         let mut updates = vec![];
+        // Tags are synced up to the latest note stored before their query
+        let latest_seq = self.database.latest_seq().await?;
         for (tag, tag_data) in &self.tags {
-            let snotes = self.database.fetch_notes(*tag, tag_data.lts).await?;
+            let snotes =
+                self.database.fetch_notes(&[*tag], tag_data.lts, self.config.max_batch).await?;
+            let truncated = self.config.max_batch.is_some_and(|max_batch| {
+                usize::try_from(max_batch).is_ok_and(|m| snotes.len() >= m)
+            });
+
             let mut cursor = tag_data.lts;
-            for snote in &snotes {
-                let lcursor = snote
-                    .created_at
-                    .timestamp_micros()
-                    .try_into()
-                    .map_err(|_| tonic::Status::internal("Timestamp too large for cursor"))?;
+            let mut notes = Vec::with_capacity(snotes.len());
+            for snote in snotes {
+                let lcursor = snote.seq.unwrap_or(cursor);
                 cursor = cursor.max(lcursor);
+                // Convert to protobuf format
+                notes.push((TransportNote::from(snote), lcursor));
             }
 
-            // Convert to protobuf format
-            let pnotes = snotes.into_iter().map(TransportNote::from).collect::<Vec<_>>();
-            let notespg = (pnotes, cursor);
-
-            if !notespg.0.is_empty() {
-                updates.push((*tag, notespg));
-            }
+            // Remaining notes of a truncated query are queried in the following polls
+            let synced_at = if truncated { cursor } else { latest_seq.max(cursor) };
+            updates.push(TagUpdate { tag: *tag, notes, cursor, synced_at });
         }
 
         Ok(updates)
     }
 
-    pub(super) fn forward_updates(&mut self, tag_notes: Vec<(NoteTag, TransportNotesPg)>) {
+    pub(super) async fn forward_updates(&mut self, updates: Vec<TagUpdate>) {
+        // Merge updates per sub, as a sub can subscribe to multiple tags
+        let mut sub_updates: BTreeMap<u64, (&Subface, TransportNotesPg)> = BTreeMap::new();
+        for update in &updates {
+            if update.notes.is_empty() {
+                continue;
+            }
+            let Some(tag_data) = self.tags.get(&update.tag) else {
+                continue;
+            };
+            for (sub_id, sub) in &tag_data.subs {
+                let (_, (sub_notes, sub_cursor)) =
+                    sub_updates.entry(*sub_id).or_insert_with(|| (sub, (vec![], 0)));
+                let in_bounds = update
+                    .notes
+                    .iter()
+                    .skip_while(|(_, cursor)| *cursor <= sub.cursor)
+                    .take_while(|(_, cursor)| sub.until.is_none_or(|until| *cursor <= until));
+                sub_notes.extend(in_bounds.map(|(note, _)| (note.clone(), update.tag)));
+                let cursor = sub.until.map_or(update.cursor, |until| update.cursor.min(until));
+                *sub_cursor = (*sub_cursor).max(cursor);
+            }
+        }
+
         let mut remove_subs = vec![];
         // Forward updates to subs
-        for (tag, notes) in tag_notes {
-            if let Some(tag_data) = self.tags.get(&tag) {
-                // Wake-up subs with `tag`
-                for (sub_id, sub_tx) in &tag_data.subs {
+        for (sub_id, (sub, update)) in sub_updates {
+            // Nothing in the bounds of the sub
+            if update.0.is_empty() {
+                continue;
+            }
+            let cursor = update.1;
+            let notes = update.0.len() as u64;
+            // On failure, whether the sub is lagging (or gone)
+            let sent = match self.config.slow_policy {
+                SlowSubscriberPolicy::Drop => {
+                    sub.tx.try_send(update).map_err(|e| matches!(e, TrySendError::Full(_)))
+                },
+                SlowSubscriberPolicy::Lag(timeout) => sub
+                    .tx
+                    .send_timeout(update, timeout)
+                    .await
+                    .map_err(|e| matches!(e, SendTimeoutError::Timeout(_))),
+            };
+            match sent {
+                // Wake-up sub
+                Ok(()) => {
+                    self.metrics.streaming_notes_delivered(notes);
+                    self.activity.insert(sub_id, SubActivity { at: Instant::now(), cursor });
+                    if let Some(waker) = self.wakers.remove(&sub_id) {
+                        waker.wake();
+                    }
+                },
+                Err(lagging) => {
+                    if lagging {
+                        sub.lagged.store(true, Ordering::Release);
+                        self.metrics.streaming_sub_dropped("slow");
+                    } else {
+                        self.metrics.streaming_sub_dropped("closed");
+                    }
+                    remove_subs.push((sub_id, sub.tags.clone()));
+                },
+            }
+        }
+        // Remove non-responding subs
+        for (sub_id, tags) in remove_subs {
+            self.remove_sub(sub_id, &tags);
+        }
+
+        // Complete bounded subs, dropping their sender ends their stream
+        let mut complete_subs = BTreeMap::new();
+        for tag_data in self.tags.values() {
+            for sub in tag_data.subs.values() {
+                let Some(until) = sub.until else {
+                    continue;
+                };
+                let synced = sub.tags.iter().all(|tag| {
+                    self.tags.get(tag).is_some_and(|tag_data| tag_data.synced_at >= until)
+                });
+                if synced {
+                    complete_subs.insert(sub.id, sub.tags.clone());
+                }
+            }
+        }
+        for (sub_id, tags) in complete_subs {
+            self.remove_sub(sub_id, &tags);
+        }
+    }
+
+    /// Send empty updates to subs without updates for the heartbeat interval
+    pub(super) fn send_heartbeats(&mut self) {
+        let Some(interval) = self.config.heartbeat_interval else {
+            return;
+        };
+        let now = Instant::now();
+        for tag_data in self.tags.values() {
+            for (sub_id, sub) in &tag_data.subs {
+                let Some(activity) = self.activity.get_mut(sub_id) else {
+                    continue;
+                };
+                if now.duration_since(activity.at) < interval {
+                    continue;
+                }
+                // A full buffer already holds updates
+                if sub.tx.try_send((vec![], activity.cursor)).is_ok() {
+                    activity.at = now;
                     if let Some(waker) = self.wakers.remove(sub_id) {
-                        if let Ok(()) = sub_tx.try_send(notes.clone()) {
+                        waker.wake();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send a page of stored notes to each bounded sub catching up
+    ///
+    /// Subs with a full buffer are skipped until they consume updates. Caught up subs join their
+    /// tags, or complete if all notes up to their bound were sent.
+    pub(super) async fn backfill_subs(&mut self) {
+        let page_size = self.config.max_batch.unwrap_or(BACKFILL_PAGE_SIZE);
+        let mut caught_up = vec![];
+        let mut remove_subs = vec![];
+        for (sub_id, sub) in &mut self.backfills {
+            let Some(until) = sub.until else {
+                continue;
+            };
+            if sub.tx.capacity() == 0 {
+                continue;
+            }
+            let fetched =
+                match self.database.fetch_notes(&sub.tags, sub.cursor, Some(page_size)).await {
+                    Ok(fetched) => fetched,
+                    Err(e) => {
+                        tracing::error!("Streamer backfill query failure: {e}");
+                        continue;
+                    },
+                };
+
+            // Whether notes in the bounds of the sub remain
+            let mut truncated = usize::try_from(page_size).is_ok_and(|m| fetched.len() >= m);
+            let mut cursor = sub.cursor;
+            let mut size = 0;
+            let mut notes = vec![];
+            for snote in fetched {
+                let seq = snote.seq.unwrap_or(cursor);
+                if seq > until {
+                    cursor = until;
+                    truncated = false;
+                    break;
+                }
+                let tag = snote.header.metadata().tag();
+                let note = TransportNote::from(snote);
+                size += note.header.len() + note.details.len();
+                if size > BACKFILL_UPDATE_SIZE && !notes.is_empty() {
+                    truncated = true;
+                    break;
+                }
+                cursor = seq;
+                notes.push((note, tag));
+            }
+
+            if !notes.is_empty() {
+                let count = notes.len() as u64;
+                match sub.tx.try_send((notes, cursor)) {
+                    Ok(()) => {
+                        self.metrics.streaming_notes_delivered(count);
+                        self.activity.insert(*sub_id, SubActivity { at: Instant::now(), cursor });
+                        if let Some(waker) = self.wakers.remove(sub_id) {
                             waker.wake();
-                        } else {
-                            remove_subs.push((*sub_id, tag));
                         }
-                    }
+                    },
+                    // Retried once the sub consumes updates
+                    Err(TrySendError::Full(_)) => continue,
+                    Err(TrySendError::Closed(_)) => {
+                        self.metrics.streaming_sub_dropped("closed");
+                        remove_subs.push((*sub_id, sub.tags.clone()));
+                        continue;
+                    },
                 }
             }
+            sub.cursor = cursor;
+
+            if cursor >= until {
+                // Complete, dropping its sender ends its stream
+                remove_subs.push((*sub_id, sub.tags.clone()));
+            } else if !truncated {
+                caught_up.push(*sub_id);
+            }
         }
-        // Remove non-responding subs
-        for (sub_id, tag) in remove_subs {
-            self.remove_sub(sub_id, tag);
+
+        for (sub_id, tags) in remove_subs {
+            self.remove_sub(sub_id, &tags);
+        }
+        // Notes stored after the cursor of the sub are queried by its tags from now on
+        for sub_id in caught_up {
+            if let Some(sub) = self.backfills.remove(&sub_id) {
+                self.join_tags(&sub);
+            }
         }
     }
 
-    pub(super) fn update_timestamps(&mut self, tag_notes: &[(NoteTag, TransportNotesPg)]) {
+    pub(super) fn update_timestamps(&mut self, updates: &[TagUpdate]) {
         // Update query cursors, to the cursor of the most recent note
-        for (tag, notes) in tag_notes {
-            if let Some(tag_data) = self.tags.get_mut(tag) {
-                tag_data.lts = notes.1;
+        for update in updates {
+            if let Some(tag_data) = self.tags.get_mut(&update.tag) {
+                tag_data.lts = update.cursor;
+                tag_data.synced_at = update.synced_at;
             }
         }
     }
@@ -138,30 +408,56 @@ impl NoteStreamerManager {
         self.wakers.insert(sub_id, waker);
     }
 
+    /// Add a sub, bounded subs first catching up with stored notes, see
+    /// [`NoteStreamerManager::backfill_subs`]
     pub fn add_sub(&mut self, sub: Subface) {
-        let entry = self.tags.entry(sub.tag).or_insert_with(TagData::new);
-        entry.subs.insert(sub.id, sub.tx);
+        self.activity
+            .insert(sub.id, SubActivity { at: Instant::now(), cursor: sub.cursor });
+        if sub.until.is_some() {
+            self.backfills.insert(sub.id, sub);
+        } else {
+            self.join_tags(&sub);
+        }
+        self.metrics.streaming_active_subscriptions(self.activity.len() as u64);
     }
 
-    pub fn remove_sub(&mut self, sub_id: u64, tag: NoteTag) {
-        let mut remove_tag = false;
-        if let Some(tag_data) = self.tags.get_mut(&tag) {
-            tag_data.subs.remove(&sub_id);
-            if tag_data.subs.is_empty() {
-                // No more subscribers for this tag
-                remove_tag = true;
-            }
+    fn join_tags(&mut self, sub: &Subface) {
+        for tag in &sub.tags {
+            let entry = self.tags.entry(*tag).or_insert_with(|| TagData::new(sub.cursor));
+            entry.subs.insert(sub.id, sub.clone());
         }
-        if remove_tag {
-            self.tags.remove(&tag);
+    }
+
+    pub fn remove_sub(&mut self, sub_id: u64, tags: &[NoteTag]) {
+        self.wakers.remove(&sub_id);
+        self.activity.remove(&sub_id);
+        self.backfills.remove(&sub_id);
+        for tag in tags {
+            let mut remove_tag = false;
+            if let Some(tag_data) = self.tags.get_mut(tag) {
+                tag_data.subs.remove(&sub_id);
+                if tag_data.subs.is_empty() {
+                    // No more subscribers for this tag
+                    remove_tag = true;
+                }
+            }
+            if remove_tag {
+                self.tags.remove(tag);
+            }
         }
+        self.metrics.streaming_active_subscriptions(self.activity.len() as u64);
     }
 }
 
 impl NoteStreamer {
-    pub(crate) fn new(database: Arc<Database>, rx: mpsc::Receiver<StreamerMessage>) -> Self {
+    pub(crate) fn new(
+        database: Arc<Database>,
+        config: StreamerConfig,
+        metrics: MetricsStreaming,
+        rx: mpsc::Receiver<StreamerMessage>,
+    ) -> Self {
         Self {
-            manager: NoteStreamerManager::new(database),
+            manager: NoteStreamerManager::new(database, config, metrics),
             rx,
         }
     }
@@ -190,13 +486,15 @@ impl NoteStreamer {
             res = manager.query_updates() => {
                 let tag_notes = res?;
                 manager.update_timestamps(&tag_notes);
-                manager.forward_updates(tag_notes);
+                manager.forward_updates(tag_notes).await;
+                manager.backfill_subs().await;
+                manager.send_heartbeats();
             }
             // Handle streamer control messages
             Some(msg) = rx.recv() => {
                 match msg {
                     StreamerMessage::AddSub(sub) => manager.add_sub(sub),
-                    StreamerMessage::RemoveSub((id, tag)) => manager.remove_sub(id, tag),
+                    StreamerMessage::RemoveSub((id, tags)) => manager.remove_sub(id, &tags),
                     StreamerMessage::Waker((id, waker)) => manager.update_waker(id, waker),
                     StreamerMessage::Shutdown => return Ok(false),
                 }
@@ -207,25 +505,57 @@ impl NoteStreamer {
 }
 
 impl Sub {
-    pub(crate) fn new(
+    pub(super) fn new(
         id: u64,
-        tag: NoteTag,
+        tags: Vec<NoteTag>,
         rx: mpsc::Receiver<TransportNotesPg>,
         streamer_tx: mpsc::Sender<StreamerMessage>,
+        lagged: Arc<AtomicBool>,
+        slot: SubscriptionSlot,
     ) -> Self {
-        Self { id, tag, rx, streamer_tx }
+        Self {
+            id,
+            tags,
+            rx,
+            streamer_tx,
+            lagged,
+            _slot: slot,
+        }
     }
 }
 
 impl Subface {
-    pub fn new(id: u64, tag: NoteTag, tx: mpsc::Sender<TransportNotesPg>) -> Self {
-        Self { id, tag, tx }
+    pub fn new(
+        id: u64,
+        tags: Vec<NoteTag>,
+        tx: mpsc::Sender<TransportNotesPg>,
+        lagged: Arc<AtomicBool>,
+        cursor: u64,
+        until: Option<u64>,
+    ) -> Self {
+        Self { id, tags, tx, lagged, cursor, until }
+    }
+}
+
+impl Default for StreamerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(500),
+            max_batch: None,
+            slow_policy: SlowSubscriberPolicy::Drop,
+            heartbeat_interval: None,
+        }
     }
 }
 
 impl TagData {
-    pub fn new() -> Self {
-        Self { lts: 0, subs: BTreeMap::new() }
+    /// Tag tracked from `cursor`, notes stored up to it not being queried
+    pub fn new(cursor: u64) -> Self {
+        Self {
+            lts: cursor,
+            synced_at: cursor,
+            subs: BTreeMap::new(),
+        }
     }
 }
 
@@ -241,10 +571,20 @@ impl tonic::codegen::tokio_stream::Stream for Sub {
         match self.rx.poll_recv(cx) {
             Poll::Ready(Some(pgnotes)) => {
                 let (notes, cursor) = pgnotes;
-                let updates = StreamNotesUpdate { notes, cursor };
+                let (notes, tags) =
+                    notes.into_iter().map(|(note, tag)| (note, tag.as_u32())).unzip();
+                let updates = StreamNotesUpdate { notes, cursor, tags };
                 return Poll::Ready(Some(Ok(updates)));
             },
-            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Ready(None) => {
+                // Terminal error for subs dropped for being too slow
+                if self.lagged.swap(false, Ordering::Acquire) {
+                    return Poll::Ready(Some(Err(tonic::Status::resource_exhausted(
+                        "Subscription dropped for not keeping up with updates",
+                    ))));
+                }
+                return Poll::Ready(None);
+            },
             _ => (),
         }
 
@@ -252,7 +592,9 @@ impl tonic::codegen::tokio_stream::Stream for Sub {
         if let Err(e) =
             self.streamer_tx.try_send(StreamerMessage::Waker((self.id, cx.waker().clone())))
         {
-            tracing::error!("Streaming waker tx failure: {e}");
+            if !matches!(e, TrySendError::Closed(_)) {
+                tracing::error!("Streaming waker tx failure: {e}");
+            }
             return Poll::Ready(None);
         }
 
@@ -262,8 +604,356 @@ impl tonic::codegen::tokio_stream::Stream for Sub {
 
 impl Drop for Sub {
     fn drop(&mut self) {
-        if let Err(e) = self.streamer_tx.try_send(StreamerMessage::RemoveSub((self.id, self.tag))) {
-            tracing::error!("Streamer remove sub control message sending error: {e}");
+        let tags = std::mem::take(&mut self.tags);
+        match self.streamer_tx.try_send(StreamerMessage::RemoveSub((self.id, tags))) {
+            // Streamer shut down, no sub to remove
+            Ok(()) | Err(TrySendError::Closed(_)) => (),
+            Err(e) => tracing::error!("Streamer remove sub control message sending error: {e}"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use miden_objects::utils::Deserializable;
+
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::test_utils::{TestMetrics, test_note_header_with_tag};
+    use crate::types::{NoteHeader, StoredNote};
+
+    /// Update of a single (dummy) note
+    fn tag_update(tag: NoteTag, cursor: u64) -> TagUpdate {
+        TagUpdate {
+            tag,
+            notes: vec![(TransportNote::default(), cursor)],
+            cursor,
+            synced_at: cursor,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_dropped() {
+        let metrics = TestMetrics::new();
+        let database = Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+            .await
+            .unwrap();
+        let mut manager = NoteStreamerManager::new(
+            Arc::new(database),
+            StreamerConfig::default(),
+            metrics.metrics.streaming.clone(),
+        );
+
+        let tag = NoteTag::from(0xc000_0000u32);
+        let (tx, mut rx) = mpsc::channel(1);
+        let lagged = Arc::new(AtomicBool::new(false));
+        manager.add_sub(Subface::new(1, vec![tag], tx, lagged.clone(), 0, None));
+
+        // The sub never polls, so its buffer fills up
+        manager.forward_updates(vec![tag_update(tag, 1)]).await;
+        manager.update_waker(1, Waker::noop().clone());
+        manager.forward_updates(vec![tag_update(tag, 2)]).await;
+
+        assert!(manager.tags.is_empty());
+        assert!(manager.wakers.is_empty());
+        assert!(lagged.load(Ordering::Acquire));
+        assert_eq!(
+            metrics.counter_with_attribute("streaming_subs_dropped_count", "reason", "slow"),
+            1
+        );
+
+        // Buffered updates are still delivered before the channel closes
+        assert_eq!(rx.recv().await.unwrap().1, 1);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_metrics() {
+        let metrics = TestMetrics::new();
+        let database = Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+            .await
+            .unwrap();
+        let mut manager = NoteStreamerManager::new(
+            Arc::new(database),
+            StreamerConfig::default(),
+            metrics.metrics.streaming.clone(),
+        );
+
+        let tag = NoteTag::from(0xc000_0000u32);
+        let (tx1, mut rx1) = mpsc::channel(8);
+        let (tx2, rx2) = mpsc::channel(8);
+        manager.add_sub(Subface::new(1, vec![tag], tx1, Arc::default(), 0, None));
+        manager.add_sub(Subface::new(2, vec![tag], tx2, Arc::default(), 0, None));
+        assert_eq!(metrics.gauge("streaming_active_subscriptions"), Some(2));
+
+        // The second sub is gone, and dropped on delivery
+        drop(rx2);
+        manager.forward_updates(vec![tag_update(tag, 1)]).await;
+        assert_eq!(rx1.recv().await.unwrap().0.len(), 1);
+        assert_eq!(metrics.counter("streaming_notes_delivered_count"), 1);
+        assert_eq!(
+            metrics.counter_with_attribute("streaming_subs_dropped_count", "reason", "closed"),
+            1
+        );
+        assert_eq!(metrics.gauge("streaming_active_subscriptions"), Some(1));
+
+        manager.remove_sub(1, &[tag]);
+        assert_eq!(metrics.gauge("streaming_active_subscriptions"), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_streamer_max_batch() {
+        let metrics = TestMetrics::new();
+        let database = Arc::new(
+            Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+                .await
+                .unwrap(),
+        );
+        let config = StreamerConfig {
+            poll_interval: Duration::from_millis(10),
+            max_batch: Some(1),
+            ..Default::default()
+        };
+        let mut manager =
+            NoteStreamerManager::new(database.clone(), config, metrics.metrics.streaming.clone());
+
+        let tag = NoteTag::from(0xc000_0000u32);
+        let (tx, mut rx) = mpsc::channel(32);
+        manager.add_sub(Subface::new(1, vec![tag], tx, Arc::new(AtomicBool::new(false)), 0, None));
+
+        let start = Utc::now();
+        let mut note_ids = vec![];
+        for i in 0..3 {
+            let note = StoredNote {
+                header: test_note_header_with_tag(tag),
+                details: vec![1, 2, 3, 4],
+                created_at: start + chrono::Duration::milliseconds(i),
+                expires_at: None,
+                seq: None,
+                format_version: 1,
+            };
+            database.store_note(&note).await.unwrap();
+            note_ids.push(note.header.id());
+        }
+
+        // One note per poll, resuming after the last delivered note
+        for note_id in note_ids {
+            let updates = manager.query_updates().await.unwrap();
+            manager.update_timestamps(&updates);
+            manager.forward_updates(updates).await;
+
+            let (notes, _cursor) = rx.try_recv().unwrap();
+            assert_eq!(notes.len(), 1);
+            let header = NoteHeader::read_from_bytes(&notes[0].0.header).unwrap();
+            assert_eq!(header.id(), note_id);
+        }
+
+        let updates = manager.query_updates().await.unwrap();
+        assert!(updates.iter().all(|update| update.notes.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_streamer_multiple_tags() {
+        let metrics = TestMetrics::new();
+        let database = Arc::new(
+            Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+                .await
+                .unwrap(),
+        );
+        let config = StreamerConfig {
+            poll_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let mut manager =
+            NoteStreamerManager::new(database.clone(), config, metrics.metrics.streaming.clone());
+
+        let tags = vec![NoteTag::from(0xc000_0001u32), NoteTag::from(0xc000_0002u32)];
+        let (tx, mut rx) = mpsc::channel(32);
+        manager.add_sub(Subface::new(
+            1,
+            tags.clone(),
+            tx,
+            Arc::new(AtomicBool::new(false)),
+            0,
+            None,
+        ));
+
+        for tag in &tags {
+            let note = StoredNote {
+                header: test_note_header_with_tag(*tag),
+                details: vec![1, 2, 3, 4],
+                created_at: Utc::now(),
+                expires_at: None,
+                seq: None,
+                format_version: 1,
+            };
+            database.store_note(&note).await.unwrap();
+        }
+
+        // Notes of both tags are merged in a single update, labeled with their tag
+        let updates = manager.query_updates().await.unwrap();
+        manager.update_timestamps(&updates);
+        manager.forward_updates(updates).await;
+        let (notes, _cursor) = rx.try_recv().unwrap();
+        assert_eq!(notes.len(), 2);
+        for (note, tag) in &notes {
+            let header = NoteHeader::read_from_bytes(&note.header).unwrap();
+            assert_eq!(header.metadata().tag(), *tag);
+        }
+        let mut note_tags = notes.iter().map(|(_, tag)| *tag).collect::<Vec<_>>();
+        note_tags.sort();
+        assert_eq!(note_tags, tags);
+
+        // The sub is removed from all its tags
+        manager.remove_sub(1, &tags);
+        assert!(manager.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_streamer_until_cursor() {
+        let metrics = TestMetrics::new();
+        let database = Arc::new(
+            Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+                .await
+                .unwrap(),
+        );
+        let config = StreamerConfig {
+            poll_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let mut manager =
+            NoteStreamerManager::new(database.clone(), config, metrics.metrics.streaming.clone());
+
+        let tag = NoteTag::from(0xc000_0000u32);
+        let start = Utc::now() - chrono::Duration::seconds(1);
+        let mut notes = vec![];
+        for i in 0..2 {
+            let note = StoredNote {
+                header: test_note_header_with_tag(tag),
+                details: vec![1, 2, 3, 4],
+                created_at: start + chrono::Duration::milliseconds(i),
+                expires_at: None,
+                seq: None,
+                format_version: 1,
+            };
+            database.store_note(&note).await.unwrap();
+            notes.push(note);
+        }
+
+        // Bounded to the first note
+        let stored = database.fetch_notes(&[tag], 0, None).await.unwrap();
+        let until = stored[0].seq.unwrap();
+        let (tx, mut rx) = mpsc::channel(32);
+        let lagged = Arc::new(AtomicBool::new(false));
+        manager.add_sub(Subface::new(1, vec![tag], tx, lagged.clone(), 0, Some(until)));
+        manager.backfill_subs().await;
+
+        let (update_notes, cursor) = rx.recv().await.unwrap();
+        assert_eq!(update_notes.len(), 1);
+        let header = NoteHeader::read_from_bytes(&update_notes[0].0.header).unwrap();
+        assert_eq!(header.id(), notes[0].header.id());
+        assert_eq!(cursor, until);
+
+        // The stream then completes, without error
+        assert!(rx.recv().await.is_none());
+        assert!(!lagged.load(Ordering::Acquire));
+        assert!(manager.backfills.is_empty());
+        assert!(manager.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_streamer_until_cursor_pages() {
+        let metrics = TestMetrics::new();
+        let database = Arc::new(
+            Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+                .await
+                .unwrap(),
+        );
+        let config = StreamerConfig {
+            poll_interval: Duration::from_millis(10),
+            max_batch: Some(1),
+            ..Default::default()
+        };
+        let mut manager =
+            NoteStreamerManager::new(database.clone(), config, metrics.metrics.streaming.clone());
+
+        let tag = NoteTag::from(0xc000_0000u32);
+        let start = Utc::now() - chrono::Duration::seconds(1);
+        for i in 0..4 {
+            let note = StoredNote {
+                header: test_note_header_with_tag(tag),
+                details: vec![1, 2, 3, 4],
+                created_at: start + chrono::Duration::milliseconds(i),
+                expires_at: None,
+                seq: None,
+                format_version: 1,
+            };
+            database.store_note(&note).await.unwrap();
+        }
+
+        // Bounded to the third note, one note per update
+        let stored = database.fetch_notes(&[tag], 0, None).await.unwrap();
+        let until = stored[2].seq.unwrap();
+        let (tx, mut rx) = mpsc::channel(1);
+        manager.add_sub(Subface::new(
+            1,
+            vec![tag],
+            tx,
+            Arc::new(AtomicBool::new(false)),
+            0,
+            Some(until),
+        ));
+
+        for snote in &stored[..3] {
+            manager.backfill_subs().await;
+            // Full buffer, nothing queried
+            manager.backfill_subs().await;
+            let (update_notes, cursor) = rx.recv().await.unwrap();
+            assert_eq!(update_notes.len(), 1);
+            assert_eq!(cursor, snote.seq.unwrap());
+        }
+
+        manager.backfill_subs().await;
+        assert!(rx.recv().await.is_none());
+        assert!(manager.backfills.is_empty());
+        assert_eq!(metrics.counter("streaming_notes_delivered_count"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_streamer_heartbeat() {
+        let metrics = TestMetrics::new();
+        let database = Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone())
+            .await
+            .unwrap();
+        let config = StreamerConfig {
+            heartbeat_interval: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        let mut manager =
+            NoteStreamerManager::new(Arc::new(database), config, metrics.metrics.streaming.clone());
+
+        let tag = NoteTag::from(0xc000_0000u32);
+        let (tx, mut rx) = mpsc::channel(32);
+        manager.add_sub(Subface::new(1, vec![tag], tx, Arc::new(AtomicBool::new(false)), 0, None));
+
+        // Not idle for long enough
+        manager.send_heartbeats();
+        assert!(rx.try_recv().is_err());
+
+        sleep(Duration::from_millis(30)).await;
+        manager.send_heartbeats();
+        let (notes, cursor) = rx.try_recv().unwrap();
+        assert!(notes.is_empty());
+        assert_eq!(cursor, 0);
+
+        // Heartbeats keep the last sent cursor
+        manager.forward_updates(vec![tag_update(tag, 5)]).await;
+        rx.try_recv().unwrap();
+        sleep(Duration::from_millis(30)).await;
+        manager.send_heartbeats();
+        let (notes, cursor) = rx.try_recv().unwrap();
+        assert!(notes.is_empty());
+        assert_eq!(cursor, 5);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+/// Limiter of open streaming subscriptions, global and per peer
+///
+/// A limit of `0` disables the respective check. Subscriptions of unknown peers are only subject
+/// to the global limit.
+pub(super) struct SubscriptionLimiter {
+    global: usize,
+    per_peer: usize,
+    state: Mutex<SubscriptionCounts>,
+}
+
+/// Reason for a rejection by the [`SubscriptionLimiter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SubscriptionLimited {
+    /// Peer limit exceeded
+    Peer,
+    /// Global limit exceeded
+    Global,
+}
+
+#[derive(Default)]
+struct SubscriptionCounts {
+    total: usize,
+    peers: BTreeMap<IpAddr, usize>,
+}
+
+/// Open subscription, released on drop
+pub(super) struct SubscriptionSlot {
+    limiter: Arc<SubscriptionLimiter>,
+    peer: Option<IpAddr>,
+}
+
+impl SubscriptionLimiter {
+    pub(super) fn new(global: usize, per_peer: usize) -> Self {
+        Self {
+            global,
+            per_peer,
+            state: Mutex::new(SubscriptionCounts::default()),
+        }
+    }
+
+    /// Open a subscription for `peer`
+    pub(super) fn acquire(
+        self: &Arc<Self>,
+        peer: Option<IpAddr>,
+    ) -> Result<SubscriptionSlot, SubscriptionLimited> {
+        let mut state = self.state.lock().expect("Subscription limiter lock poisoned");
+
+        if self.global != 0 && state.total >= self.global {
+            return Err(SubscriptionLimited::Global);
+        }
+        if let Some(peer) = peer {
+            let count = state.peers.entry(peer).or_default();
+            if self.per_peer != 0 && *count >= self.per_peer {
+                return Err(SubscriptionLimited::Peer);
+            }
+            *count += 1;
+        }
+        state.total += 1;
+
+        Ok(SubscriptionSlot { limiter: self.clone(), peer })
+    }
+
+    fn release(&self, peer: Option<IpAddr>) {
+        let mut state = self.state.lock().expect("Subscription limiter lock poisoned");
+        state.total = state.total.saturating_sub(1);
+        if let Some(peer) = peer {
+            if let Some(count) = state.peers.get_mut(&peer) {
+                *count -= 1;
+                if *count == 0 {
+                    state.peers.remove(&peer);
+                }
+            }
+        }
+    }
+
+    /// Number of open subscriptions
+    #[cfg(test)]
+    pub(super) fn open(&self) -> usize {
+        self.state.lock().expect("Subscription limiter lock poisoned").total
+    }
+}
+
+impl Drop for SubscriptionSlot {
+    fn drop(&mut self) {
+        self.limiter.release(self.peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_limiter_per_peer() {
+        let limiter = Arc::new(SubscriptionLimiter::new(3, 2));
+        let peer_a = IpAddr::from([10, 0, 0, 1]);
+        let peer_b = IpAddr::from([10, 0, 0, 2]);
+
+        let slot = limiter.acquire(Some(peer_a)).unwrap();
+        let _other = limiter.acquire(Some(peer_a)).unwrap();
+        assert_eq!(limiter.acquire(Some(peer_a)).err(), Some(SubscriptionLimited::Peer));
+        let _peer_b = limiter.acquire(Some(peer_b)).unwrap();
+        assert_eq!(limiter.acquire(None).err(), Some(SubscriptionLimited::Global));
+
+        // Closed subscriptions are released
+        drop(slot);
+        assert_eq!(limiter.open(), 2);
+        assert!(limiter.acquire(Some(peer_a)).is_ok());
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::types::{NoteHeader, NoteTag, NoteType};
+
+/// Policy for the tags of sent notes
+///
+/// Notes not allowed by the policy are rejected before being stored.
+#[derive(Clone, Default)]
+pub enum TagPolicy {
+    /// Allow all notes
+    #[default]
+    AllowAll,
+    /// Allow only private notes
+    RequirePrivate,
+    /// Allow only notes with a tag satisfying the predicate
+    Custom(Arc<dyn Fn(NoteTag) -> bool + Send + Sync>),
+}
+
+impl TagPolicy {
+    /// Whether the note with `header` is allowed
+    pub fn allows(&self, header: &NoteHeader) -> bool {
+        match self {
+            Self::AllowAll => true,
+            Self::RequirePrivate => header.metadata().note_type() == NoteType::Private,
+            Self::Custom(predicate) => predicate(header.metadata().tag()),
+        }
+    }
+}
+
+impl fmt::Debug for TagPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AllowAll => f.write_str("AllowAll"),
+            Self::RequirePrivate => f.write_str("RequirePrivate"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+/// gRPC server TLS configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// Server certificate chain, PEM-encoded
+    pub server_cert: PathBuf,
+    /// Server private key, PEM-encoded
+    pub server_key: PathBuf,
+    /// CA certificate authenticating clients, PEM-encoded
+    ///
+    /// If set, clients without a certificate issued by this CA are rejected (mTLS).
+    pub client_ca: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Load the certificates and key into a tonic [`ServerTlsConfig`]
+    pub(super) fn server_config(&self) -> crate::Result<ServerTlsConfig> {
+        let cert = std::fs::read(&self.server_cert)?;
+        let key = std::fs::read(&self.server_key)?;
+        let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        if let Some(client_ca) = &self.client_ca {
+            let ca = std::fs::read(client_ca)?;
+            config = config.client_ca_root(Certificate::from_pem(ca));
+        }
+        Ok(config)
+    }
+}
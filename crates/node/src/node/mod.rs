@@ -1,14 +1,20 @@
 use std::sync::Arc;
 
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use tokio::sync::watch;
 use tracing::{error, info};
 
 use self::grpc::{GrpcServer, GrpcServerConfig};
+use self::prometheus::{PrometheusReader, PrometheusServer};
 use crate::Result;
 use crate::database::{Database, DatabaseConfig, DatabaseMaintenance};
-use crate::metrics::Metrics;
+use crate::metrics::{Metrics, MetricsConfig};
 
 /// gRPC server
 pub mod grpc;
+/// Prometheus metrics endpoint
+pub mod prometheus;
 
 /// Miden Note Transport Node
 pub struct Node {
@@ -16,8 +22,12 @@ pub struct Node {
     grpc: GrpcServer,
     /// Database maintenance
     maintenance: DatabaseMaintenance,
+    /// Prometheus metrics endpoint
+    prometheus: Option<PrometheusServer>,
     /// Metrics
     _metrics: Metrics,
+    /// Metrics export, kept alive for the node lifetime
+    _meter_provider: SdkMeterProvider,
 
     // To be used in other services, .e.g. P2P
     _database: Arc<Database>,
@@ -30,34 +40,106 @@ pub struct NodeConfig {
     pub grpc: GrpcServerConfig,
     /// Database configuration
     pub database: DatabaseConfig,
+    /// Metrics export configuration
+    pub metrics: MetricsConfig,
 }
 
 impl Node {
     /// Node constructor
     pub async fn init(config: NodeConfig) -> Result<Self> {
-        let metrics = Metrics::default();
+        let prometheus_reader = config.metrics.prometheus_port.map(|_| PrometheusReader::default());
+        let meter_provider = config.metrics.meter_provider(prometheus_reader.clone())?;
+        let metrics =
+            Metrics::new(&meter_provider.meter("miden-note-transport-node"), &config.metrics);
+        let prometheus =
+            config.metrics.prometheus_port.zip(prometheus_reader).map(|(port, reader)| {
+                PrometheusServer::new(config.grpc.host.clone(), port, reader)
+            });
+
         let database =
             Arc::new(Database::connect(config.database.clone(), metrics.db.clone()).await?);
 
-        let grpc = GrpcServer::new(database.clone(), config.grpc, metrics.grpc.clone());
+        let grpc = GrpcServer::new(
+            database.clone(),
+            config.grpc,
+            metrics.grpc.clone(),
+            metrics.streaming.clone(),
+        );
         let maintenance =
             DatabaseMaintenance::new(database.clone(), config.database, metrics.db.clone());
 
         Ok(Self {
             grpc,
             maintenance,
+            prometheus,
             _metrics: metrics,
+            _meter_provider: meter_provider,
             _database: database,
         })
     }
 
     /// Node running-task
+    ///
+    /// Runs until a SIGINT or SIGTERM is received, after which in-flight requests are drained and
+    /// the remaining tasks stopped.
     pub async fn entrypoint(self) {
         info!("Starting Miden Transport Node");
-        tokio::spawn(self.maintenance.entrypoint());
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let maintenance = tokio::spawn(self.maintenance.entrypoint(shutdown_rx.clone()));
+        if let Some(prometheus) = self.prometheus {
+            let mut shutdown_rx = shutdown_rx.clone();
+            let shutdown = async move {
+                let _ = shutdown_rx.wait_for(|stop| *stop).await;
+            };
+            tokio::spawn(async move {
+                if let Err(e) = prometheus.serve(shutdown).await {
+                    error!("Prometheus endpoint error: {e}");
+                }
+            });
+        }
 
-        if let Err(e) = self.grpc.serve().await {
+        if let Err(e) = self.grpc.serve(shutdown_signal()).await {
             error!("Server error: {e}");
         }
+
+        // Stop the remaining tasks
+        let _ = shutdown_tx.send(true);
+        if let Err(e) = maintenance.await {
+            error!("Database maintenance task failure: {e}");
+        }
+        info!("Miden Transport Node stopped");
+    }
+}
+
+/// Completes on SIGINT (Ctrl-C) or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            },
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            },
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
     }
+    info!("Shutdown signal received");
 }
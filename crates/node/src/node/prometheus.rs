@@ -0,0 +1,281 @@
+use std::fmt::{Display, Write};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use axum::Router;
+use axum::http::StatusCode;
+use axum::routing::get;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData, ResourceMetrics};
+use opentelemetry_sdk::metrics::reader::MetricReader;
+use opentelemetry_sdk::metrics::{InstrumentKind, ManualReader, Pipeline, Temporality};
+use tokio::net::TcpListener;
+
+/// Metrics reader collecting the current values on demand, rendered in the Prometheus text format
+///
+/// Collection does not involve the other readers of the meter provider.
+#[derive(Debug, Clone)]
+pub struct PrometheusReader {
+    reader: Arc<ManualReader>,
+}
+
+/// HTTP server exposing metrics on `/metrics` in the Prometheus text format
+pub struct PrometheusServer {
+    host: String,
+    port: u16,
+    reader: PrometheusReader,
+}
+
+impl Default for PrometheusReader {
+    fn default() -> Self {
+        let reader = ManualReader::builder().with_temporality(Temporality::Cumulative).build();
+        Self { reader: Arc::new(reader) }
+    }
+}
+
+impl PrometheusReader {
+    /// Collect the current values, rendered
+    pub fn render(&self) -> crate::Result<String> {
+        let mut metrics = ResourceMetrics::default();
+        self.reader
+            .collect(&mut metrics)
+            .map_err(|e| crate::Error::Internal(format!("Metrics collection: {e}")))?;
+        Ok(render(&metrics))
+    }
+}
+
+impl MetricReader for PrometheusReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.reader.register_pipeline(pipeline);
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> OTelSdkResult {
+        self.reader.collect(rm)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.reader.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.reader.shutdown_with_timeout(timeout)
+    }
+
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.reader.temporality(kind)
+    }
+}
+
+impl PrometheusServer {
+    /// [`PrometheusServer`] constructor
+    ///
+    /// `reader` must be registered as a reader of the meter provider.
+    pub fn new(host: String, port: u16, reader: PrometheusReader) -> Self {
+        Self { host, port, reader }
+    }
+
+    /// Serve metrics, until `shutdown` completes
+    pub async fn serve(
+        self,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> crate::Result<()> {
+        let addr = format!("{}:{}", self.host, self.port)
+            .parse::<SocketAddr>()
+            .map_err(|e| crate::Error::Internal(format!("Invalid address: {e}")))?;
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_with_listener(listener, shutdown).await
+    }
+
+    async fn serve_with_listener(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> crate::Result<()> {
+        let Self { reader, .. } = self;
+        let app = Router::new().route(
+            "/metrics",
+            get(move || {
+                let reader = reader.clone();
+                async move {
+                    reader.render().map_err(|e| {
+                        tracing::error!("Prometheus metrics rendering failure: {e}");
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })
+                }
+            }),
+        );
+
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Render metrics in the Prometheus text exposition format
+fn render(metrics: &ResourceMetrics) -> String {
+    let mut out = String::new();
+    for metric in metrics.scope_metrics().flat_map(|scope_metrics| scope_metrics.metrics()) {
+        let name = metric.name();
+        let _ = writeln!(out, "# HELP {name} {}", metric.description());
+        match metric.data() {
+            AggregatedMetrics::U64(data) => render_data(&mut out, name, data),
+            AggregatedMetrics::I64(data) => render_data(&mut out, name, data),
+            AggregatedMetrics::F64(data) => render_data(&mut out, name, data),
+        }
+    }
+    out
+}
+
+fn render_data<T: Display + Copy>(out: &mut String, name: &str, data: &MetricData<T>) {
+    match data {
+        MetricData::Gauge(gauge) => {
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            for point in gauge.data_points() {
+                let labels = labels(point.attributes(), None);
+                let _ = writeln!(out, "{name}{labels} {}", point.value());
+            }
+        },
+        MetricData::Sum(sum) => {
+            let kind = if sum.is_monotonic() { "counter" } else { "gauge" };
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for point in sum.data_points() {
+                let labels = labels(point.attributes(), None);
+                let _ = writeln!(out, "{name}{labels} {}", point.value());
+            }
+        },
+        MetricData::Histogram(histogram) => {
+            let _ = writeln!(out, "# TYPE {name} histogram");
+            for point in histogram.data_points() {
+                // Prometheus buckets are cumulative, OpenTelemetry ones are not
+                let mut cumulative = 0;
+                for (bound, count) in point.bounds().zip(point.bucket_counts()) {
+                    cumulative += count;
+                    let labels = labels(point.attributes(), Some(&bound.to_string()));
+                    let _ = writeln!(out, "{name}_bucket{labels} {cumulative}");
+                }
+                let labels_inf = labels(point.attributes(), Some("+Inf"));
+                let _ = writeln!(out, "{name}_bucket{labels_inf} {}", point.count());
+                let labels = labels(point.attributes(), None);
+                let _ = writeln!(out, "{name}_sum{labels} {}", point.sum());
+                let _ = writeln!(out, "{name}_count{labels} {}", point.count());
+            }
+        },
+        // Not used by the node metrics
+        MetricData::ExponentialHistogram(_) => {},
+    }
+}
+
+fn labels<'a>(attributes: impl Iterator<Item = &'a KeyValue>, le: Option<&str>) -> String {
+    let mut labels: Vec<String> = attributes
+        .map(|kv| {
+            let key: String = kv
+                .key
+                .as_str()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            format!("{key}=\"{}\"", escape(&kv.value.as_str()))
+        })
+        .collect();
+    if let Some(le) = le {
+        labels.push(format!("le=\"{le}\""));
+    }
+
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use miden_note_transport_proto::miden_note_transport::miden_note_transport_server::MidenNoteTransport;
+    use miden_note_transport_proto::miden_note_transport::{SendNoteRequest, TransportNote};
+    use miden_objects::utils::Serializable;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::database::{Database, DatabaseConfig};
+    use crate::metrics::{Metrics, MetricsConfig};
+    use crate::node::grpc::{GrpcServer, GrpcServerConfig};
+    use crate::test_utils::test_note_header;
+
+    async fn scrape(addr: SocketAddr) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_scrape() {
+        let reader = PrometheusReader::default();
+        let provider = SdkMeterProvider::builder().with_reader(reader.clone()).build();
+        let metrics = Metrics::new(&provider.meter("test"), &MetricsConfig::default());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = PrometheusServer::new(addr.ip().to_string(), addr.port(), reader);
+        tokio::spawn(server.serve_with_listener(listener, std::future::pending()));
+
+        let database =
+            Database::connect(DatabaseConfig::default(), metrics.db.clone()).await.unwrap();
+        let grpc = GrpcServer::new(
+            Arc::new(database),
+            GrpcServerConfig::default(),
+            metrics.grpc.clone(),
+            metrics.streaming.clone(),
+        );
+        let note = TransportNote {
+            header: test_note_header().to_bytes(),
+            details: vec![1, 2, 3, 4],
+            expires_at: None,
+            format_version: 0,
+        };
+        grpc.send_note(tonic::Request::new(SendNoteRequest {
+            note: Some(note.clone()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+        let response = scrape(addr).await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("# TYPE grpc_send_note_count counter"));
+        assert!(response.contains("grpc_send_note_count{operation=\"grpc.send_note.request\"} 1"));
+        assert!(response.contains("grpc_send_note_duration_bucket{"));
+
+        // Every scrape collects the current values
+        grpc.send_note(tonic::Request::new(SendNoteRequest {
+            note: Some(TransportNote {
+                header: test_note_header().to_bytes(),
+                ..note
+            }),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+        let (response, concurrent_response) = tokio::join!(scrape(addr), scrape(addr));
+        for response in [response, concurrent_response] {
+            assert!(
+                response.contains("grpc_send_note_count{operation=\"grpc.send_note.request\"} 2")
+            );
+        }
+    }
+}
@@ -1,8 +1,128 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use miden_note_transport_proto::miden_note_transport::miden_note_transport_client::MidenNoteTransportClient;
+use miden_note_transport_proto::miden_note_transport::miden_note_transport_server::MidenNoteTransportServer;
 use miden_objects::account::AccountId;
 use miden_objects::note::{NoteExecutionHint, NoteHeader, NoteId, NoteMetadata, NoteTag, NoteType};
 use miden_objects::testing::account_id::ACCOUNT_ID_MAX_ZEROES;
 use miden_objects::{Felt, Word};
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
 use rand::Rng;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tonic::server::NamedService;
+use tonic::transport::Channel;
+use tonic_health::pb::HealthCheckRequest;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+
+use crate::clock::Clock;
+use crate::database::{Database, DatabaseConfig};
+use crate::metrics::{Metrics, MetricsConfig};
+use crate::node::grpc::{GrpcServer, GrpcServerConfig};
+
+/// [`Clock`] only advancing when requested
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// Create a new instance of `MockClock`, starting at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// Advance the clock by `duration`
+    pub fn advance(&self, duration: chrono::Duration) {
+        *self.now.lock().expect("Mock clock lock poisoned") += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("Mock clock lock poisoned")
+    }
+}
+
+/// Time waited for a spawned test server to be serving
+pub const TEST_SERVER_READY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Node gRPC server running in-process, with an in-memory database
+///
+/// The server is stopped on drop.
+pub struct TestServer {
+    /// Bound address
+    pub addr: SocketAddr,
+    /// Database of the server
+    pub database: Arc<Database>,
+    /// Metrics of the server
+    pub metrics: TestMetrics,
+    handle: JoinHandle<crate::Result<()>>,
+}
+
+impl TestServer {
+    /// Connect a client to the server
+    pub async fn client(&self) -> MidenNoteTransportClient<Channel> {
+        MidenNoteTransportClient::connect(format!("http://{}", self.addr))
+            .await
+            .unwrap()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Spawn a node gRPC server, see [`TestServer`]
+///
+/// The server listens on the configured host and port, a port of `0` being assigned by the OS.
+/// Returns once the server reports itself as serving through the gRPC health check, or an error
+/// if it is not ready within [`TEST_SERVER_READY_TIMEOUT`].
+pub async fn spawn_test_server(config: GrpcServerConfig) -> crate::Result<TestServer> {
+    let metrics = TestMetrics::new();
+    let database =
+        Arc::new(Database::connect(DatabaseConfig::default(), metrics.metrics.db.clone()).await?);
+
+    let listener = TcpListener::bind((config.host.as_str(), config.port)).await?;
+    let addr = listener.local_addr()?;
+    let server = GrpcServer::new(
+        database.clone(),
+        config,
+        metrics.metrics.grpc.clone(),
+        metrics.metrics.streaming.clone(),
+    );
+    let handle = tokio::spawn(server.serve_with_listener(listener, std::future::pending()));
+
+    let server = TestServer { addr, database, metrics, handle };
+    tokio::time::timeout(TEST_SERVER_READY_TIMEOUT, wait_serving(addr))
+        .await
+        .map_err(|_| crate::Error::Internal(format!("Test server at {addr} not ready")))?;
+    Ok(server)
+}
+
+/// Poll the gRPC health check until the note transport service is serving
+async fn wait_serving(addr: SocketAddr) {
+    let request = HealthCheckRequest {
+        service: <MidenNoteTransportServer<GrpcServer> as NamedService>::NAME.to_string(),
+    };
+    loop {
+        if let Ok(mut client) = HealthClient::connect(format!("http://{addr}")).await {
+            let status = client.check(request.clone()).await;
+            if status.is_ok_and(|response| response.into_inner().status() == ServingStatus::Serving)
+            {
+                return;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
 
 /// Generate a random [`NoteId`]
 pub fn random_note_id() -> NoteId {
@@ -26,10 +146,15 @@ pub fn random_note_id() -> NoteId {
 
 /// Generate a private [`NoteHeader`] with random sender
 pub fn test_note_header() -> NoteHeader {
+    let sender = AccountId::try_from(ACCOUNT_ID_MAX_ZEROES).unwrap();
+    test_note_header_with_tag(NoteTag::from_account_id(sender))
+}
+
+/// Generate a private [`NoteHeader`] with random sender and the provided [`NoteTag`]
+pub fn test_note_header_with_tag(tag: NoteTag) -> NoteHeader {
     let id = random_note_id();
     let sender = AccountId::try_from(ACCOUNT_ID_MAX_ZEROES).unwrap();
     let note_type = NoteType::Private;
-    let tag = NoteTag::from_account_id(sender);
     let aux = Felt::try_from(0xffff_ffff_0000_0000u64).unwrap();
     let execution_hint = NoteExecutionHint::None;
 
@@ -37,3 +162,115 @@ pub fn test_note_header() -> NoteHeader {
 
     NoteHeader::new(id, metadata)
 }
+
+/// [`Metrics`] exported to memory, allowing recorded values to be inspected
+pub struct TestMetrics {
+    /// Metrics to be passed to the tested component
+    pub metrics: Metrics,
+    provider: SdkMeterProvider,
+    exporter: InMemoryMetricExporter,
+}
+
+impl TestMetrics {
+    /// Create a new instance of `TestMetrics`
+    pub fn new() -> Self {
+        Self::with_config(&MetricsConfig::default())
+    }
+
+    /// Create a new instance of `TestMetrics` with the provided configuration
+    pub fn with_config(config: &MetricsConfig) -> Self {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter.clone()).build())
+            .build();
+        let metrics = Metrics::new(&provider.meter("test"), config);
+
+        Self { metrics, provider, exporter }
+    }
+
+    /// Bucket boundaries of a histogram, empty if not recorded
+    pub fn histogram_bounds(&self, name: &str) -> Vec<f64> {
+        self.provider.force_flush().unwrap();
+        let exported = self.exporter.get_finished_metrics().unwrap();
+
+        let Some(resource_metrics) = exported.last() else {
+            return vec![];
+        };
+        resource_metrics
+            .scope_metrics()
+            .flat_map(|scope_metrics| scope_metrics.metrics())
+            .filter(|metric| metric.name() == name)
+            .find_map(|metric| match metric.data() {
+                AggregatedMetrics::U64(MetricData::Histogram(histogram)) => {
+                    histogram.data_points().next().map(|point| point.bounds().collect())
+                },
+                AggregatedMetrics::F64(MetricData::Histogram(histogram)) => {
+                    histogram.data_points().next().map(|point| point.bounds().collect())
+                },
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Current value of a `u64` counter, summed over all attributes
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counter_with(name, |_| true)
+    }
+
+    /// Current value of a `u64` counter, summed over the data points with a matching attribute
+    pub fn counter_with_attribute(&self, name: &str, key: &str, value: &str) -> u64 {
+        self.counter_with(name, |attributes| {
+            attributes.iter().any(|kv| kv.key.as_str() == key && kv.value.as_str() == value)
+        })
+    }
+
+    /// Last recorded value of a `u64` gauge, if recorded
+    pub fn gauge(&self, name: &str) -> Option<u64> {
+        self.provider.force_flush().unwrap();
+        let exported = self.exporter.get_finished_metrics().unwrap();
+
+        let resource_metrics = exported.last()?;
+        resource_metrics
+            .scope_metrics()
+            .flat_map(|scope_metrics| scope_metrics.metrics())
+            .filter(|metric| metric.name() == name)
+            .find_map(|metric| match metric.data() {
+                AggregatedMetrics::U64(MetricData::Gauge(gauge)) => {
+                    gauge.data_points().next().map(|point| point.value())
+                },
+                _ => None,
+            })
+    }
+
+    fn counter_with<F>(&self, name: &str, filter: F) -> u64
+    where
+        F: Fn(&[&opentelemetry::KeyValue]) -> bool,
+    {
+        self.provider.force_flush().unwrap();
+        let exported = self.exporter.get_finished_metrics().unwrap();
+
+        // Counters are cumulative, so the most recent export holds the current value
+        let Some(resource_metrics) = exported.last() else {
+            return 0;
+        };
+        resource_metrics
+            .scope_metrics()
+            .flat_map(|scope_metrics| scope_metrics.metrics())
+            .filter(|metric| metric.name() == name)
+            .map(|metric| match metric.data() {
+                AggregatedMetrics::U64(MetricData::Sum(sum)) => sum
+                    .data_points()
+                    .filter(|point| filter(&point.attributes().collect::<Vec<_>>()))
+                    .map(|point| point.value())
+                    .sum(),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl Default for TestMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -14,6 +14,9 @@ pub use miden_objects::note::{
 };
 use miden_objects::utils::Serializable;
 
+/// Current format version of note details
+pub const NOTE_FORMAT_VERSION: u8 = 1;
+
 /// A note stored in the database
 #[derive(Debug, Clone)]
 pub struct StoredNote {
@@ -25,6 +28,19 @@ pub struct StoredNote {
     pub details: Vec<u8>,
     /// Reference timestamp
     pub created_at: DateTime<Utc>,
+    /// Expiration timestamp
+    ///
+    /// Expired notes are no longer served and are removed on database maintenance.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Sequence number, the pagination cursor of the note
+    ///
+    /// Assigned by the database on storage, strictly increasing in storage order. Ignored when
+    /// storing a note.
+    pub seq: Option<u64>,
+    /// Format version of the details, see [`NOTE_FORMAT_VERSION`]
+    ///
+    /// Allows recipients to decode details stored by earlier versions.
+    pub format_version: u8,
 }
 
 impl From<StoredNote> for TransportNote {
@@ -32,6 +48,8 @@ impl From<StoredNote> for TransportNote {
         Self {
             header: snote.header.to_bytes(),
             details: snote.details,
+            expires_at: snote.expires_at.map(datetime_to_proto_timestamp),
+            format_version: u32::from(snote.format_version),
         }
     }
 }
@@ -48,3 +66,14 @@ pub fn proto_timestamp_to_datetime(pts: prost_types::Timestamp) -> anyhow::Resul
 
     Ok(dts)
 }
+
+/// Helper converter from `DateTime<Utc>` to [`prost_types::Timestamp`]
+pub fn datetime_to_proto_timestamp(dts: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: dts.timestamp(),
+        nanos: dts
+            .timestamp_subsec_nanos()
+            .try_into()
+            .expect("Sub-second nanoseconds always fit in i32"),
+    }
+}
@@ -9,23 +9,50 @@ pub struct TransportNote {
     /// NoteDetails, can be encrypted
     #[prost(bytes = "vec", tag = "2")]
     pub details: ::prost::alloc::vec::Vec<u8>,
+    /// Optional expiration time, after which the note is no longer served
+    #[prost(message, optional, tag = "3")]
+    pub expires_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// Format version of the details, 0 for the current version
+    #[prost(uint32, tag = "4")]
+    pub format_version: u32,
 }
 /// API request for sending a note
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SendNoteRequest {
     #[prost(message, optional, tag = "1")]
     pub note: ::core::option::Option<TransportNote>,
+    /// Optional key identifying retries of the same request, the first response is returned
+    /// for all of them during a server-configured time, even with distinct notes. At most 128 bytes
+    #[prost(string, tag = "2")]
+    pub idempotency_key: ::prost::alloc::string::String,
 }
 /// API response for sending a note
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct SendNoteResponse {}
+pub struct SendNoteResponse {
+    #[prost(enumeration = "SendNoteStatus", tag = "1")]
+    pub status: i32,
+}
 /// API request for fetching notes
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct FetchNotesRequest {
     #[prost(fixed32, repeated, tag = "1")]
     pub tags: ::prost::alloc::vec::Vec<u32>,
+    /// Cursor returned by a previous fetch, notes stored after it are returned. 0 for all notes
     #[prost(fixed64, tag = "2")]
     pub cursor: u64,
+    /// Maximum number of notes returned, across all tags. 0 for unlimited
+    #[prost(uint32, tag = "3")]
+    pub limit: u32,
+    /// Exclude notes acknowledged with AckNotes
+    #[prost(bool, tag = "4")]
+    pub exclude_acked: bool,
+    /// Include the number of notes remaining after the returned page, ascending order only
+    #[prost(bool, tag = "5")]
+    pub include_total: bool,
+    /// Order of the returned notes. In descending order, notes stored before the cursor are
+    /// returned, and a cursor of 0 stands for the most recent note
+    #[prost(enumeration = "FetchOrder", tag = "6")]
+    pub order: i32,
 }
 /// API response for fetching notes
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -35,14 +62,36 @@ pub struct FetchNotesResponse {
     /// Transport Layer pagination
     #[prost(fixed64, tag = "2")]
     pub cursor: u64,
+    /// Number of notes remaining after the returned cursor, if requested
+    #[prost(uint64, optional, tag = "3")]
+    pub total_remaining: ::core::option::Option<u64>,
+}
+/// API request for fetching notes as a stream of pages
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct FetchNotesStreamRequest {
+    #[prost(fixed32, repeated, tag = "1")]
+    pub tags: ::prost::alloc::vec::Vec<u32>,
+    #[prost(fixed64, tag = "2")]
+    pub cursor: u64,
+    /// Maximum number of notes per page. 0 for the server default
+    #[prost(uint32, tag = "3")]
+    pub page_size: u32,
 }
 /// API request for streaming notes
-#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct StreamNotesRequest {
     #[prost(fixed32, tag = "1")]
     pub tag: u32,
+    /// Lower cursor bound, only notes stored after it are streamed
     #[prost(fixed64, tag = "2")]
     pub cursor: u64,
+    /// Tags subscribed to on a single stream, `tag` is ignored if not empty
+    #[prost(fixed32, repeated, tag = "3")]
+    pub tags: ::prost::alloc::vec::Vec<u32>,
+    /// Upper cursor bound, the stream completes once all notes up to it are delivered.
+    /// 0 for an unbounded stream
+    #[prost(fixed64, tag = "4")]
+    pub until_cursor: u64,
 }
 /// API response for streaming notes updates
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -52,6 +101,9 @@ pub struct StreamNotesUpdate {
     /// Transport Layer pagination
     #[prost(fixed64, tag = "2")]
     pub cursor: u64,
+    /// Subscribed tag of each note, in the order of `notes`
+    #[prost(fixed32, repeated, tag = "3")]
+    pub tags: ::prost::alloc::vec::Vec<u32>,
 }
 /// Server statistics
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -60,6 +112,7 @@ pub struct StatsResponse {
     pub total_notes: u64,
     #[prost(uint64, tag = "2")]
     pub total_tags: u64,
+    /// Per-tag statistics, limited to the tags with the most notes
     #[prost(message, repeated, tag = "3")]
     pub notes_per_tag: ::prost::alloc::vec::Vec<TagStats>,
 }
@@ -73,6 +126,158 @@ pub struct TagStats {
     #[prost(message, optional, tag = "3")]
     pub last_activity: ::core::option::Option<::prost_types::Timestamp>,
 }
+/// API request for acknowledging received notes
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AckNotesRequest {
+    /// Serialized NoteIds
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub note_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// API response for acknowledging received notes
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AckNotesResponse {
+    /// Number of newly acknowledged notes
+    #[prost(uint64, tag = "1")]
+    pub acked: u64,
+}
+/// API request for checking which notes are stored
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ExistsNotesRequest {
+    /// Serialized NoteIds
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub note_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// API response for checking which notes are stored
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ExistsNotesResponse {
+    /// Serialized NoteIds of the requested notes that are stored
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub note_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// API request for a note by ID
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetNoteRequest {
+    /// Serialized NoteId
+    #[prost(bytes = "vec", tag = "1")]
+    pub note_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// API response for a note by ID
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetNoteResponse {
+    #[prost(message, optional, tag = "1")]
+    pub note: ::core::option::Option<TransportNote>,
+}
+/// API request for listing the tags of stored notes
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListTagsRequest {
+    /// Maximum number of tags returned. 0 for the server default
+    #[prost(uint32, tag = "1")]
+    pub limit: u32,
+    /// Number of tags skipped, in ascending tag order
+    #[prost(uint32, tag = "2")]
+    pub offset: u32,
+}
+/// API response for listing the tags of stored notes
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListTagsResponse {
+    /// Distinct tags of stored notes, in ascending order
+    #[prost(fixed32, repeated, tag = "1")]
+    pub tags: ::prost::alloc::vec::Vec<u32>,
+}
+/// API request for the number of notes stored over time
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct NoteHistogramRequest {
+    /// Bucket duration in seconds, buckets being aligned on the Unix epoch
+    #[prost(uint32, tag = "1")]
+    pub bucket_secs: u32,
+    /// Start of the time range, inclusive. Unset for all stored notes
+    #[prost(message, optional, tag = "2")]
+    pub from: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the time range, exclusive. Unset for the current time
+    #[prost(message, optional, tag = "3")]
+    pub to: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Number of notes stored during a time bucket
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct NoteHistogramBucket {
+    /// Start of the bucket
+    #[prost(message, optional, tag = "1")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag = "2")]
+    pub count: u64,
+}
+/// API response for the number of notes stored over time
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct NoteHistogramResponse {
+    /// Non-empty buckets, in ascending order
+    #[prost(message, repeated, tag = "1")]
+    pub buckets: ::prost::alloc::vec::Vec<NoteHistogramBucket>,
+}
+/// Outcome of a send note request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SendNoteStatus {
+    Unspecified = 0,
+    /// Note stored
+    Accepted = 1,
+    /// Note already stored, the request had no effect
+    Duplicate = 2,
+    /// Note refused. Invalid notes are currently reported through gRPC errors instead
+    Rejected = 3,
+}
+impl SendNoteStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "SEND_NOTE_STATUS_UNSPECIFIED",
+            Self::Accepted => "SEND_NOTE_STATUS_ACCEPTED",
+            Self::Duplicate => "SEND_NOTE_STATUS_DUPLICATE",
+            Self::Rejected => "SEND_NOTE_STATUS_REJECTED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SEND_NOTE_STATUS_UNSPECIFIED" => Some(Self::Unspecified),
+            "SEND_NOTE_STATUS_ACCEPTED" => Some(Self::Accepted),
+            "SEND_NOTE_STATUS_DUPLICATE" => Some(Self::Duplicate),
+            "SEND_NOTE_STATUS_REJECTED" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+/// Order of fetched notes, by cursor
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FetchOrder {
+    /// Oldest notes first
+    Asc = 0,
+    /// Most recent notes first
+    Desc = 1,
+}
+impl FetchOrder {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Asc => "FETCH_ORDER_ASC",
+            Self::Desc => "FETCH_ORDER_DESC",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FETCH_ORDER_ASC" => Some(Self::Asc),
+            "FETCH_ORDER_DESC" => Some(Self::Desc),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod miden_note_transport_client {
     #![allow(
@@ -255,6 +460,36 @@ pub mod miden_note_transport_client {
                 );
             self.inner.server_streaming(req, path, codec).await
         }
+        /// Fetch all notes for the provided tags as a stream of pages, ending once exhausted
+        pub async fn fetch_notes_stream(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FetchNotesStreamRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::FetchNotesResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/miden_note_transport.MidenNoteTransport/FetchNotesStream",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "miden_note_transport.MidenNoteTransport",
+                        "FetchNotesStream",
+                    ),
+                );
+            self.inner.server_streaming(req, path, codec).await
+        }
         /// Get server statistics
         pub async fn stats(
             &mut self,
@@ -279,6 +514,156 @@ pub mod miden_note_transport_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Acknowledge received notes, if enabled on the server
+        pub async fn ack_notes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AckNotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AckNotesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/miden_note_transport.MidenNoteTransport/AckNotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "miden_note_transport.MidenNoteTransport",
+                        "AckNotes",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Check which of the provided notes are stored
+        pub async fn exists_notes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExistsNotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExistsNotesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/miden_note_transport.MidenNoteTransport/ExistsNotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "miden_note_transport.MidenNoteTransport",
+                        "ExistsNotes",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// List the distinct tags of stored notes, if authentication is enabled on the server
+        pub async fn list_tags(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTagsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTagsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/miden_note_transport.MidenNoteTransport/ListTags",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "miden_note_transport.MidenNoteTransport",
+                        "ListTags",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Count notes stored over time, if authentication is enabled on the server
+        pub async fn note_histogram(
+            &mut self,
+            request: impl tonic::IntoRequest<super::NoteHistogramRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::NoteHistogramResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/miden_note_transport.MidenNoteTransport/NoteHistogram",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "miden_note_transport.MidenNoteTransport",
+                        "NoteHistogram",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Get a note by ID
+        pub async fn get_note(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetNoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetNoteResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/miden_note_transport.MidenNoteTransport/GetNote",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "miden_note_transport.MidenNoteTransport",
+                        "GetNote",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -324,11 +709,65 @@ pub mod miden_note_transport_server {
             tonic::Response<Self::StreamNotesStream>,
             tonic::Status,
         >;
+        /// Server streaming response type for the FetchNotesStream method.
+        type FetchNotesStreamStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::FetchNotesResponse, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Fetch all notes for the provided tags as a stream of pages, ending once exhausted
+        async fn fetch_notes_stream(
+            &self,
+            request: tonic::Request<super::FetchNotesStreamRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::FetchNotesStreamStream>,
+            tonic::Status,
+        >;
         /// Get server statistics
         async fn stats(
             &self,
             request: tonic::Request<()>,
         ) -> std::result::Result<tonic::Response<super::StatsResponse>, tonic::Status>;
+        /// Acknowledge received notes, if enabled on the server
+        async fn ack_notes(
+            &self,
+            request: tonic::Request<super::AckNotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AckNotesResponse>,
+            tonic::Status,
+        >;
+        /// Check which of the provided notes are stored
+        async fn exists_notes(
+            &self,
+            request: tonic::Request<super::ExistsNotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExistsNotesResponse>,
+            tonic::Status,
+        >;
+        /// List the distinct tags of stored notes, if authentication is enabled on the server
+        async fn list_tags(
+            &self,
+            request: tonic::Request<super::ListTagsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTagsResponse>,
+            tonic::Status,
+        >;
+        /// Count notes stored over time, if authentication is enabled on the server
+        async fn note_histogram(
+            &self,
+            request: tonic::Request<super::NoteHistogramRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::NoteHistogramResponse>,
+            tonic::Status,
+        >;
+        /// Get a note by ID
+        async fn get_note(
+            &self,
+            request: tonic::Request<super::GetNoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetNoteResponse>,
+            tonic::Status,
+        >;
     }
     /// gRPC service definition
     #[derive(Debug)]
@@ -545,6 +984,53 @@ pub mod miden_note_transport_server {
                     };
                     Box::pin(fut)
                 }
+                "/miden_note_transport.MidenNoteTransport/FetchNotesStream" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchNotesStreamSvc<T: MidenNoteTransport>(pub Arc<T>);
+                    impl<
+                        T: MidenNoteTransport,
+                    > tonic::server::ServerStreamingService<super::FetchNotesStreamRequest>
+                    for FetchNotesStreamSvc<T> {
+                        type Response = super::FetchNotesResponse;
+                        type ResponseStream = T::FetchNotesStreamStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FetchNotesStreamRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MidenNoteTransport>::fetch_notes_stream(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchNotesStreamSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/miden_note_transport.MidenNoteTransport/Stats" => {
                     #[allow(non_camel_case_types)]
                     struct StatsSvc<T: MidenNoteTransport>(pub Arc<T>);
@@ -585,6 +1071,236 @@ pub mod miden_note_transport_server {
                     };
                     Box::pin(fut)
                 }
+                "/miden_note_transport.MidenNoteTransport/AckNotes" => {
+                    #[allow(non_camel_case_types)]
+                    struct AckNotesSvc<T: MidenNoteTransport>(pub Arc<T>);
+                    impl<
+                        T: MidenNoteTransport,
+                    > tonic::server::UnaryService<super::AckNotesRequest>
+                    for AckNotesSvc<T> {
+                        type Response = super::AckNotesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AckNotesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MidenNoteTransport>::ack_notes(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = AckNotesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/miden_note_transport.MidenNoteTransport/ExistsNotes" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExistsNotesSvc<T: MidenNoteTransport>(pub Arc<T>);
+                    impl<
+                        T: MidenNoteTransport,
+                    > tonic::server::UnaryService<super::ExistsNotesRequest>
+                    for ExistsNotesSvc<T> {
+                        type Response = super::ExistsNotesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExistsNotesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MidenNoteTransport>::exists_notes(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ExistsNotesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/miden_note_transport.MidenNoteTransport/ListTags" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListTagsSvc<T: MidenNoteTransport>(pub Arc<T>);
+                    impl<
+                        T: MidenNoteTransport,
+                    > tonic::server::UnaryService<super::ListTagsRequest>
+                    for ListTagsSvc<T> {
+                        type Response = super::ListTagsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListTagsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MidenNoteTransport>::list_tags(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListTagsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/miden_note_transport.MidenNoteTransport/NoteHistogram" => {
+                    #[allow(non_camel_case_types)]
+                    struct NoteHistogramSvc<T: MidenNoteTransport>(pub Arc<T>);
+                    impl<
+                        T: MidenNoteTransport,
+                    > tonic::server::UnaryService<super::NoteHistogramRequest>
+                    for NoteHistogramSvc<T> {
+                        type Response = super::NoteHistogramResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::NoteHistogramRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MidenNoteTransport>::note_histogram(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = NoteHistogramSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/miden_note_transport.MidenNoteTransport/GetNote" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetNoteSvc<T: MidenNoteTransport>(pub Arc<T>);
+                    impl<
+                        T: MidenNoteTransport,
+                    > tonic::server::UnaryService<super::GetNoteRequest>
+                    for GetNoteSvc<T> {
+                        type Response = super::GetNoteResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetNoteRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MidenNoteTransport>::get_note(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetNoteSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(